@@ -8,6 +8,8 @@ fn main() {
 
     tonic_build::configure()
         .out_dir("src/pb")
+        // served by the reflection service
+        .file_descriptor_set_path("src/pb/reservation_descriptor.bin")
         .with_sqlx_type(&["reservation.ReservationStatus"])
         .with_derive_builder(&[
             "reservation.ReservationQuery",
@@ -15,13 +17,37 @@ fn main() {
         ])
         .with_derive_builder_into(
             "reservation.ReservationQuery",
-            &["resource_id", "user_id", "status", "desc"],
+            &[
+                "resource_id",
+                "user_id",
+                "status",
+                "desc",
+                "resource_ids",
+                "user_ids",
+                "statuses",
+                "match_mode",
+                "limit",
+                "resume_token",
+            ],
         )
         .with_derive_builder_into(
             "reservation.ReservationFilter",
-            &["resource_id", "user_id", "status", "desc"],
+            &[
+                "resource_id",
+                "user_id",
+                "status",
+                "desc",
+                "include_total",
+                "approximate_total",
+                "page_token",
+                "sort_by",
+                "note_query",
+                "resource_ids",
+                "user_ids",
+                "statuses",
+            ],
         )
-        .with_derive_builder_option("reservation.ReservationFilter", &["cursor"])
+        .with_derive_builder_option("reservation.ReservationFilter", &["cursor", "start", "end"])
         .with_derive_builder_option("reservation.ReservationQuery", &["start", "end"])
         .with_type_attributes(
             &[
@@ -34,6 +60,34 @@ fn main() {
             &["page_size"],
             &["#[builder(setter(into), default = \"10\")]"],
         )
+        // JSON of the REST gateway
+        .with_serde(
+            &[
+                "reservation.Reservation",
+                "reservation.FilterPager",
+                "reservation.FilterResponse",
+            ],
+            true,
+            true,
+        )
+        .with_type_attributes(&["reservation.Reservation"], &["#[serde(default)]"])
+        .with_field_attributes(
+            &[
+                "reservation.Reservation.start",
+                "reservation.Reservation.end",
+                "reservation.Reservation.expires_at",
+                "reservation.Reservation.created_at",
+            ],
+            &[r#"#[serde(with = "crate::utils::rfc3339")]"#],
+        )
+        .with_field_attributes(
+            &["reservation.Reservation.status"],
+            &[r#"#[serde(with = "crate::utils::status_name")]"#],
+        )
+        .with_field_attributes(
+            &["reservation.Reservation.resume_token"],
+            &[r#"#[serde(skip_serializing_if = "String::is_empty")]"#],
+        )
         .compile_with_config(config, &["protos/reservation.proto"], &["protos"])
         // .compile(&["protos/reservation.proto"], &["protos"])
         .unwrap();
@@ -2,12 +2,24 @@ use std::{fs, path::Path};
 
 use serde::{Deserialize, Serialize};
 
-use crate::Error;
+use crate::{Error, Validator};
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
 pub struct Config {
     pub db: DbConfig,
     pub server: ServerConfig,
+    #[serde(default)]
+    pub rsvp: RsvpConfig,
+    /// bearer tokens required by the server, which refuses to start without unless
+    /// `insecure_disable_auth` is set
+    #[serde(default)]
+    pub auth: Option<AuthConfig>,
+    /// serve calls without a token as an admin of the default tenant, for local development
+    /// only
+    #[serde(default)]
+    pub insecure_disable_auth: bool,
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
@@ -29,6 +41,206 @@ fn default_max_connections() -> u32 {
 pub struct ServerConfig {
     pub host: String,
     pub port: u16,
+    /// plaintext if not set
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+    /// how long in-flight calls may finish after SIGTERM or SIGINT, streams still open
+    /// are then ended
+    #[serde(default = "default_drain_timeout_seconds")]
+    pub drain_timeout_seconds: u64,
+    /// port of the Prometheus metrics on the same host, not served if not set
+    #[serde(default)]
+    pub metrics_port: Option<u16>,
+    /// port of the REST/JSON gateway on the same host, not served if not set
+    #[serde(default)]
+    pub http_port: Option<u16>,
+    /// gRPC-Web for browser clients, not accepted if not set
+    #[serde(default)]
+    pub grpc_web: Option<GrpcWebConfig>,
+}
+
+fn default_drain_timeout_seconds() -> u64 {
+    30
+}
+
+/// certificates of the server, reloaded when their files change
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct TlsConfig {
+    /// path of the PEM encoded certificate chain
+    pub cert: String,
+    /// path of the PEM encoded private key
+    pub key: String,
+    /// path of the PEM encoded CA certificates of the clients. Clients must present a
+    /// certificate signed by them if set
+    #[serde(default)]
+    pub client_ca: Option<String>,
+    /// roles of the callers identified by their client certificate
+    #[serde(default)]
+    pub client_roles: Vec<String>,
+    /// how often the files are checked for changes
+    #[serde(default = "default_reload_interval_seconds")]
+    pub reload_interval_seconds: u64,
+}
+
+fn default_reload_interval_seconds() -> u64 {
+    10
+}
+
+/// gRPC-Web calls over HTTP/1.1, with CORS for the pages of other origins
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct GrpcWebConfig {
+    /// origins of the pages allowed to call, e.g. `https://app.example.com`, `*` for any.
+    /// Only pages of the same origin may call if empty
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
+    /// how long browsers may cache the preflight responses
+    #[serde(default = "default_cors_max_age_seconds")]
+    pub max_age_seconds: u64,
+}
+
+fn default_cors_max_age_seconds() -> u64 {
+    24 * 60 * 60
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct RsvpConfig {
+    /// how long a pending reservation holds its slot, 0 means forever
+    #[serde(default = "default_hold_ttl_seconds")]
+    pub hold_ttl_seconds: u32,
+    /// how often the expired holds are reaped
+    #[serde(default = "default_reaper_interval_seconds")]
+    pub reaper_interval_seconds: u64,
+    /// max holds expired in one batch, with one transaction for the holds of each resource
+    #[serde(default = "default_reaper_batch_size")]
+    pub reaper_batch_size: i64,
+    /// key to sign page and resume tokens. A random key is used if not set, which should be
+    /// set if several instances serve the same clients
+    #[serde(default = "default_page_token_secret")]
+    pub page_token_secret: String,
+    /// reservations buffered for each streaming query before the client reads them
+    #[serde(default = "default_query_buffer_size")]
+    pub query_buffer_size: usize,
+    /// streaming queries are cancelled after this many seconds
+    #[serde(default = "default_query_timeout_seconds")]
+    pub query_timeout_seconds: u64,
+    /// max streaming queries running at the same time, more are rejected
+    #[serde(default = "default_max_concurrent_queries")]
+    pub max_concurrent_queries: usize,
+}
+
+fn default_hold_ttl_seconds() -> u32 {
+    15 * 60
+}
+
+fn default_reaper_interval_seconds() -> u64 {
+    30
+}
+
+fn default_reaper_batch_size() -> i64 {
+    100
+}
+
+fn default_query_buffer_size() -> usize {
+    128
+}
+
+fn default_query_timeout_seconds() -> u64 {
+    5 * 60
+}
+
+fn default_max_concurrent_queries() -> usize {
+    64
+}
+
+fn default_page_token_secret() -> String {
+    let key: [u8; 32] = rand::random();
+    key.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct AuthConfig {
+    pub algorithm: JwtAlgorithm,
+    /// shared secret of HS256
+    #[serde(default)]
+    pub secret: String,
+    /// PEM encoded public key of RS256
+    #[serde(default)]
+    pub public_key: String,
+    /// expected `iss` claim, not checked if not set
+    #[serde(default)]
+    pub issuer: Option<String>,
+    /// expected `aud` claim, not checked if not set
+    #[serde(default)]
+    pub audience: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub enum JwtAlgorithm {
+    HS256,
+    RS256,
+}
+
+/// where the spans of the calls and their SQL statements go
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct TelemetryConfig {
+    #[serde(default)]
+    pub exporter: TelemetryExporter,
+    /// gRPC endpoint of the OpenTelemetry collector
+    #[serde(default = "default_otlp_endpoint")]
+    pub otlp_endpoint: String,
+    /// `service.name` of the exported spans
+    #[serde(default = "default_service_name")]
+    pub service_name: String,
+    /// e.g. `info,reservation=debug`, overridden by `RUST_LOG`
+    #[serde(default = "default_filter")]
+    pub filter: String,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TelemetryExporter {
+    /// JSON lines on stdout, one for each event and closed span
+    #[default]
+    Json,
+    /// OTLP over gRPC
+    Otlp,
+}
+
+fn default_otlp_endpoint() -> String {
+    "http://localhost:4317".to_string()
+}
+
+fn default_service_name() -> String {
+    "reservation".to_string()
+}
+
+fn default_filter() -> String {
+    "info".to_string()
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            exporter: TelemetryExporter::default(),
+            otlp_endpoint: default_otlp_endpoint(),
+            service_name: default_service_name(),
+            filter: default_filter(),
+        }
+    }
+}
+
+impl Default for RsvpConfig {
+    fn default() -> Self {
+        Self {
+            hold_ttl_seconds: default_hold_ttl_seconds(),
+            reaper_interval_seconds: default_reaper_interval_seconds(),
+            reaper_batch_size: default_reaper_batch_size(),
+            page_token_secret: default_page_token_secret(),
+            query_buffer_size: default_query_buffer_size(),
+            query_timeout_seconds: default_query_timeout_seconds(),
+            max_concurrent_queries: default_max_concurrent_queries(),
+        }
+    }
 }
 
 impl ServerConfig {
@@ -42,7 +254,26 @@ impl ServerConfig {
 impl Config {
     pub fn load(filename: impl AsRef<Path>) -> Result<Self, Error> {
         let config = fs::read_to_string(filename).map_err(|_| Error::ConfigReadError)?;
-        serde_yaml::from_str(&config).map_err(|_| Error::ConfigParseError)
+        let config: Self = serde_yaml::from_str(&config).map_err(|_| Error::ConfigParseError)?;
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+impl Validator for Config {
+    fn validate(&self) -> Result<(), Error> {
+        if self.auth.is_none() && !self.insecure_disable_auth {
+            return Err(Error::InvalidConfig(
+                "auth is not set, set insecure_disable_auth to serve calls without tokens".into(),
+            ));
+        }
+
+        // anyone could sign tokens with an empty key, leave it out for a random one
+        if self.rsvp.page_token_secret.is_empty() {
+            return Err(Error::InvalidConfig("page_token_secret is empty".into()));
+        }
+
+        Ok(())
     }
 }
 
@@ -84,9 +315,54 @@ mod tests {
                 },
                 server: ServerConfig {
                     host: "0.0.0.0".to_string(),
-                    port: 50051
-                }
+                    port: 50051,
+                    tls: None,
+                    drain_timeout_seconds: 30,
+                    metrics_port: None,
+                    http_port: None,
+                    grpc_web: None,
+                },
+                rsvp: RsvpConfig {
+                    hold_ttl_seconds: 600,
+                    reaper_interval_seconds: 30,
+                    reaper_batch_size: 100,
+                    page_token_secret: "page token secret for tests".to_string(),
+                    query_buffer_size: 128,
+                    query_timeout_seconds: 300,
+                    max_concurrent_queries: 64,
+                },
+                auth: Some(AuthConfig {
+                    algorithm: JwtAlgorithm::HS256,
+                    secret: "jwt secret for tests".to_string(),
+                    public_key: String::new(),
+                    issuer: Some("reservation tests".to_string()),
+                    audience: None,
+                }),
+                insecure_disable_auth: false,
+                telemetry: TelemetryConfig {
+                    exporter: TelemetryExporter::Otlp,
+                    otlp_endpoint: "http://localhost:4317".to_string(),
+                    service_name: "reservation".to_string(),
+                    filter: "info,sqlx=warn".to_string(),
+                },
             }
         )
     }
+
+    #[test]
+    fn config_without_auth_should_be_rejected() {
+        let mut config = Config::load("../service/fixtures/config.yml").unwrap();
+        config.auth = None;
+        assert!(matches!(config.validate(), Err(Error::InvalidConfig(_))));
+
+        config.insecure_disable_auth = true;
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn config_with_empty_page_token_secret_should_be_rejected() {
+        let mut config = Config::load("../service/fixtures/config.yml").unwrap();
+        config.rsvp.page_token_secret = String::new();
+        assert!(matches!(config.validate(), Err(Error::InvalidConfig(_))));
+    }
 }
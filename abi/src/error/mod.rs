@@ -18,6 +18,9 @@ pub enum Error {
     #[error("config parse error")]
     ConfigParseError,
 
+    #[error("invalid config: {0}")]
+    InvalidConfig(String),
+
     #[error("Conflict reservation: {0}")]
     ConflictReservation(String),
 
@@ -36,12 +39,96 @@ pub enum Error {
     #[error("invalid page size {0}")]
     InvalidPageSize(i64),
 
-    #[error("invalid cursor {0}")]
-    InvalidCursor(i64),
+    #[error("invalid cursor: {0}")]
+    InvalidCursor(String),
 
     #[error("invalid reservation status {0}")]
     InvalidStatus(i32),
 
+    #[error("invalid reservation status name {0}")]
+    InvalidStatusName(String),
+
+    #[error("invalid sort key {0}")]
+    InvalidSortBy(i32),
+
+    #[error("invalid match mode {0}")]
+    InvalidMatchMode(i32),
+
+    #[error("invalid limit {0}")]
+    InvalidLimit(i64),
+
+    #[error("resource {0} not found")]
+    ResourceNotFound(String),
+
+    #[error("resource {0} still has reservations")]
+    ResourceInUse(String),
+
+    #[error("invalid reservation quantity {0}")]
+    InvalidQuantity(i32),
+
+    #[error("invalid resource capacity {0}")]
+    InvalidCapacity(i32),
+
+    #[error("invalid buffer time {0}")]
+    InvalidBuffer(i32),
+
+    #[error("invalid hold ttl {0}")]
+    InvalidHoldTtl(i32),
+
+    #[error("invalid timezone {0}")]
+    InvalidTimezone(String),
+
+    #[error("invalid resource attributes {0}")]
+    InvalidAttributes(String),
+
+    #[error("invalid booking rules: {0}")]
+    InvalidBookingRules(String),
+
+    #[error("reservation lasts {0} minutes, shorter than the minimum {1} minutes")]
+    DurationTooShort(i64, i32),
+
+    #[error("reservation lasts {0} minutes, longer than the maximum {1} minutes")]
+    DurationTooLong(i64, i32),
+
+    #[error("reservation starts in {0} minutes, later than the maximum lead time {1} minutes")]
+    TooFarInAdvance(i64, i32),
+
+    #[error("reservation starts in {0} minutes, less than the minimum notice {1} minutes")]
+    InsufficientNotice(i64, i32),
+
+    #[error("reservation {0} is outside of the hours of operation {1}")]
+    OutsideOperatingHours(String, String),
+
+    #[error("reservation start {0} is not aligned to {1} minutes slots")]
+    MisalignedStart(String, i32),
+
+    #[error("reservation {0} is expired")]
+    ReservationExpired(i64),
+
+    #[error("invalid quota: {0}")]
+    InvalidQuota(String),
+
+    #[error("invalid stats query: {0}")]
+    InvalidStatsQuery(String),
+
+    #[error("quota exceeded: {0}")]
+    QuotaExceeded(String),
+
+    #[error("permission denied: {0}")]
+    PermissionDenied(String),
+
+    #[error("too many concurrent queries, at most {0} are allowed")]
+    TooManyQueries(usize),
+
+    #[error("query was not finished in {0} seconds")]
+    QueryTimeout(u64),
+
+    #[error("database migrations are not applied: {0:?}")]
+    MigrationsPending(Vec<i64>),
+
+    #[error("server is shutting down")]
+    ShuttingDown,
+
     #[error("unknown error")]
     Unknown,
 }
@@ -51,11 +138,43 @@ impl PartialEq for Error {
         match (self, other) {
             (Self::DbError(_), Self::DbError(_)) => true,
             (Self::NotFound, Self::NotFound) => true,
+            (Self::InvalidConfig(v1), Self::InvalidConfig(v2)) => v1 == v2,
             (Self::ConflictReservation(v1), Self::ConflictReservation(v2)) => v1 == v2,
             (Self::InvalidTime, Self::InvalidTime) => true,
+            (Self::InvalidStatusName(v1), Self::InvalidStatusName(v2)) => v1 == v2,
             (Self::InvalidReservationId(v1), Self::InvalidReservationId(v2)) => v1 == v2,
             (Self::InvalidUserId(v1), Self::InvalidUserId(v2)) => v1 == v2,
             (Self::InvalidResourceId(v1), Self::InvalidResourceId(v2)) => v1 == v2,
+            (Self::ResourceNotFound(v1), Self::ResourceNotFound(v2)) => v1 == v2,
+            (Self::ResourceInUse(v1), Self::ResourceInUse(v2)) => v1 == v2,
+            (Self::InvalidQuantity(v1), Self::InvalidQuantity(v2)) => v1 == v2,
+            (Self::InvalidCapacity(v1), Self::InvalidCapacity(v2)) => v1 == v2,
+            (Self::InvalidBuffer(v1), Self::InvalidBuffer(v2)) => v1 == v2,
+            (Self::InvalidHoldTtl(v1), Self::InvalidHoldTtl(v2)) => v1 == v2,
+            (Self::InvalidTimezone(v1), Self::InvalidTimezone(v2)) => v1 == v2,
+            (Self::InvalidAttributes(v1), Self::InvalidAttributes(v2)) => v1 == v2,
+            (Self::InvalidBookingRules(v1), Self::InvalidBookingRules(v2)) => v1 == v2,
+            (Self::DurationTooShort(a1, b1), Self::DurationTooShort(a2, b2)) => {
+                a1 == a2 && b1 == b2
+            }
+            (Self::DurationTooLong(a1, b1), Self::DurationTooLong(a2, b2)) => a1 == a2 && b1 == b2,
+            (Self::TooFarInAdvance(a1, b1), Self::TooFarInAdvance(a2, b2)) => a1 == a2 && b1 == b2,
+            (Self::InsufficientNotice(a1, b1), Self::InsufficientNotice(a2, b2)) => {
+                a1 == a2 && b1 == b2
+            }
+            (Self::OutsideOperatingHours(a1, b1), Self::OutsideOperatingHours(a2, b2)) => {
+                a1 == a2 && b1 == b2
+            }
+            (Self::MisalignedStart(a1, b1), Self::MisalignedStart(a2, b2)) => a1 == a2 && b1 == b2,
+            (Self::ReservationExpired(v1), Self::ReservationExpired(v2)) => v1 == v2,
+            (Self::InvalidQuota(v1), Self::InvalidQuota(v2)) => v1 == v2,
+            (Self::InvalidStatsQuery(v1), Self::InvalidStatsQuery(v2)) => v1 == v2,
+            (Self::QuotaExceeded(v1), Self::QuotaExceeded(v2)) => v1 == v2,
+            (Self::PermissionDenied(v1), Self::PermissionDenied(v2)) => v1 == v2,
+            (Self::TooManyQueries(v1), Self::TooManyQueries(v2)) => v1 == v2,
+            (Self::QueryTimeout(v1), Self::QueryTimeout(v2)) => v1 == v2,
+            (Self::MigrationsPending(v1), Self::MigrationsPending(v2)) => v1 == v2,
+            (Self::ShuttingDown, Self::ShuttingDown) => true,
             (Self::Unknown, Self::Unknown) => true,
             _ => false,
         }
@@ -83,26 +202,63 @@ impl From<sqlx::Error> for Error {
 impl From<Error> for tonic::Status {
     fn from(e: Error) -> Self {
         match e {
-            Error::DbError(_) | Error::ConfigReadError | Error::ConfigParseError => {
-                tonic::Status::internal(e.to_string())
-            }
+            Error::DbError(_)
+            | Error::ConfigReadError
+            | Error::ConfigParseError
+            | Error::InvalidConfig(_) => tonic::Status::internal(e.to_string()),
 
             Error::ConflictReservation(info) => {
                 tonic::Status::failed_precondition(format!("Conflict Reservation: {info:?}"))
             }
 
+            Error::ResourceInUse(_)
+            | Error::DurationTooShort(_, _)
+            | Error::DurationTooLong(_, _)
+            | Error::TooFarInAdvance(_, _)
+            | Error::InsufficientNotice(_, _)
+            | Error::OutsideOperatingHours(_, _)
+            | Error::MisalignedStart(_, _)
+            | Error::ReservationExpired(_) => tonic::Status::failed_precondition(e.to_string()),
+
             Error::InvalidTime
             | Error::InvalidResourceId(_)
             | Error::InvalidUserId(_)
             | Error::InvalidReservationId(_)
             | Error::InvalidPageSize(_)
             | Error::InvalidCursor(_)
-            | Error::InvalidStatus(_) => tonic::Status::invalid_argument(e.to_string()),
+            | Error::InvalidStatus(_)
+            | Error::InvalidStatusName(_)
+            | Error::InvalidSortBy(_)
+            | Error::InvalidMatchMode(_)
+            | Error::InvalidLimit(_)
+            | Error::InvalidQuantity(_)
+            | Error::InvalidCapacity(_)
+            | Error::InvalidBuffer(_)
+            | Error::InvalidHoldTtl(_)
+            | Error::InvalidTimezone(_)
+            | Error::InvalidAttributes(_)
+            | Error::InvalidBookingRules(_)
+            | Error::InvalidQuota(_)
+            | Error::InvalidStatsQuery(_) => tonic::Status::invalid_argument(e.to_string()),
+
+            Error::QuotaExceeded(_) | Error::TooManyQueries(_) => {
+                tonic::Status::resource_exhausted(e.to_string())
+            }
+
+            Error::QueryTimeout(_) => tonic::Status::deadline_exceeded(e.to_string()),
+
+            Error::MigrationsPending(_) | Error::ShuttingDown => {
+                tonic::Status::unavailable(e.to_string())
+            }
+
+            Error::PermissionDenied(_) => tonic::Status::permission_denied(e.to_string()),
 
             Error::NotFound => {
                 tonic::Status::not_found("No reservatoin found by the given condition")
             }
 
+            Error::ResourceNotFound(_) => tonic::Status::not_found(e.to_string()),
+
             Error::Unknown => tonic::Status::unknown("Unknown error"),
         }
     }
@@ -4,8 +4,10 @@ mod pb;
 mod types;
 mod utils;
 
+use sqlx::{Postgres, QueryBuilder};
+
 pub use config::*;
-pub use error::Error;
+pub use error::*;
 pub use pb::*;
 pub use types::*;
 pub use utils::*;
@@ -13,6 +15,8 @@ pub use utils::*;
 pub type ReservationId = i64;
 pub type UserId = String;
 pub type ResourceId = String;
+/// rows of a tenant are isolated from other tenants, `""` is the default tenant
+pub type TenantId = String;
 
 pub trait Validator {
     fn validate(&self) -> Result<(), Error>;
@@ -29,7 +33,13 @@ pub trait Normalize: Validator {
 }
 
 pub trait ToSql {
-    fn to_sql(&self) -> String;
+    /// the query on the rows of the tenant, with its bind values
+    fn to_query(&self, tenant_id: &str) -> QueryBuilder<'static, Postgres>;
+
+    /// the query on the rows of the default tenant
+    fn to_sql(&self) -> String {
+        self.to_query("").into_sql()
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
@@ -39,6 +49,7 @@ pub enum RsvpStatus {
     Pending,
     Confirmed,
     Blocked,
+    Expired,
 }
 
 impl Validator for ReservationId {
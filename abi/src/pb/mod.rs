@@ -2,3 +2,6 @@
 mod reservation;
 
 pub use reservation::*;
+
+/// encoded descriptors of reservation.proto, for gRPC server reflection
+pub const FILE_DESCRIPTOR_SET: &[u8] = include_bytes!("reservation_descriptor.bin");
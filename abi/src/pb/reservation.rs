@@ -1,3 +1,5 @@
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Reservation {
@@ -6,17 +8,35 @@ pub struct Reservation {
     #[prost(string, tag = "2")]
     pub user_id: ::prost::alloc::string::String,
     #[prost(enumeration = "ReservationStatus", tag = "3")]
+    #[serde(with = "crate::utils::status_name")]
     pub status: i32,
     /// resource reservation window
     #[prost(string, tag = "4")]
     pub resource_id: ::prost::alloc::string::String,
     #[prost(message, optional, tag = "5")]
+    #[serde(with = "crate::utils::rfc3339")]
     pub start: ::core::option::Option<::prost_types::Timestamp>,
     #[prost(message, optional, tag = "6")]
+    #[serde(with = "crate::utils::rfc3339")]
     pub end: ::core::option::Option<::prost_types::Timestamp>,
     /// extra data
     #[prost(string, tag = "7")]
     pub note: ::prost::alloc::string::String,
+    /// units of the resource claimed by the reservation, default to 1
+    #[prost(int32, tag = "8")]
+    pub quantity: i32,
+    /// pending reservation is expired if not confirmed before this time
+    #[prost(message, optional, tag = "9")]
+    #[serde(with = "crate::utils::rfc3339")]
+    pub expires_at: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(message, optional, tag = "10")]
+    #[serde(with = "crate::utils::rfc3339")]
+    pub created_at: ::core::option::Option<::prost_types::Timestamp>,
+    /// token to resume a query after this reservation, only set on the reservations
+    /// streamed by query
+    #[prost(string, tag = "11")]
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub resume_token: ::prost::alloc::string::String,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -105,6 +125,28 @@ pub struct ReservationQuery {
     #[prost(bool, tag = "6")]
     #[builder(setter(into), default)]
     pub desc: bool,
+    /// match any of these, in addition to resource_id, user_id and status
+    #[prost(string, repeated, tag = "7")]
+    #[builder(setter(into), default)]
+    pub resource_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, repeated, tag = "8")]
+    #[builder(setter(into), default)]
+    pub user_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(enumeration = "ReservationStatus", repeated, tag = "9")]
+    #[builder(setter(into), default)]
+    pub statuses: ::prost::alloc::vec::Vec<i32>,
+    #[prost(enumeration = "ReservationMatchMode", tag = "10")]
+    #[builder(setter(into), default)]
+    pub match_mode: i32,
+    /// stop after this many reservations, 0 for no limit
+    #[prost(int64, tag = "11")]
+    #[builder(setter(into), default)]
+    pub limit: i64,
+    /// resume after the last received reservation, e.g. after a disconnect, with its
+    /// resume_token. Tokens are signed by the server
+    #[prost(string, tag = "12")]
+    #[builder(setter(into), default)]
+    pub resume_token: ::prost::alloc::string::String,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -130,7 +172,7 @@ pub struct ReservationFilter {
     #[prost(enumeration = "ReservationStatus", tag = "3")]
     #[builder(setter(into), default)]
     pub status: i32,
-    /// cursor
+    /// cursor, deprecated in favor of page_token
     #[prost(int64, optional, tag = "4")]
     #[builder(setter(into, strip_option), default)]
     pub cursor: ::core::option::Option<i64>,
@@ -142,6 +184,45 @@ pub struct ReservationFilter {
     #[prost(bool, tag = "6")]
     #[builder(setter(into), default)]
     pub desc: bool,
+    /// count all matching reservations in pager.total
+    #[prost(bool, tag = "7")]
+    #[builder(setter(into), default)]
+    pub include_total: bool,
+    /// with include_total, estimate the count from the planner statistics instead of counting,
+    /// which is much cheaper for large tables
+    #[prost(bool, tag = "8")]
+    #[builder(setter(into), default)]
+    pub approximate_total: bool,
+    /// opaque token from FilterPager.prev_token or next_token, takes precedence over cursor.
+    /// It is only valid for the same filter conditions
+    #[prost(string, tag = "9")]
+    #[builder(setter(into), default)]
+    pub page_token: ::prost::alloc::string::String,
+    /// sort key, the sort direction is given by desc
+    #[prost(enumeration = "ReservationSortBy", tag = "10")]
+    #[builder(setter(into), default)]
+    pub sort_by: i32,
+    /// only reservations overlapping [start, end), either bound may be omitted
+    #[prost(message, optional, tag = "11")]
+    #[builder(setter(into, strip_option), default)]
+    pub start: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(message, optional, tag = "12")]
+    #[builder(setter(into, strip_option), default)]
+    pub end: ::core::option::Option<::prost_types::Timestamp>,
+    /// full-text search on the note, in web search syntax, e.g. `wheelchair -stairs`
+    #[prost(string, tag = "13")]
+    #[builder(setter(into), default)]
+    pub note_query: ::prost::alloc::string::String,
+    /// match any of these, in addition to resource_id, user_id and status
+    #[prost(string, repeated, tag = "14")]
+    #[builder(setter(into), default)]
+    pub resource_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, repeated, tag = "15")]
+    #[builder(setter(into), default)]
+    pub user_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(enumeration = "ReservationStatus", repeated, tag = "16")]
+    #[builder(setter(into), default)]
+    pub statuses: ::prost::alloc::vec::Vec<i32>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -149,16 +230,24 @@ pub struct FilterRequest {
     #[prost(message, optional, tag = "1")]
     pub filter: ::core::option::Option<ReservationFilter>,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FilterPager {
+    /// raw cursors, deprecated in favor of the page tokens
     #[prost(int64, optional, tag = "1")]
     pub prev: ::core::option::Option<i64>,
     #[prost(int64, optional, tag = "2")]
     pub next: ::core::option::Option<i64>,
     #[prost(int64, optional, tag = "3")]
     pub total: ::core::option::Option<i64>,
+    /// signed tokens to fetch the previous and next pages, empty if there is no such page
+    #[prost(string, tag = "4")]
+    pub prev_token: ::prost::alloc::string::String,
+    #[prost(string, tag = "5")]
+    pub next_token: ::prost::alloc::string::String,
 }
+#[derive(serde::Serialize, serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct FilterResponse {
@@ -167,6 +256,245 @@ pub struct FilterResponse {
     #[prost(message, optional, tag = "2")]
     pub pager: ::core::option::Option<FilterPager>,
 }
+/// a bookable resource, e.g. a meeting room or a parking space
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Resource {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    /// resource type, e.g. "meeting-room", "parking-lot"
+    #[prost(string, tag = "2")]
+    pub resource_type: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub display_name: ::prost::alloc::string::String,
+    /// units of the resource which could be reserved at the same time
+    #[prost(int32, tag = "4")]
+    pub capacity: i32,
+    /// IANA timezone of the resource, e.g. "America/Los_Angeles"
+    #[prost(string, tag = "5")]
+    pub timezone: ::prost::alloc::string::String,
+    /// extra attributes, encoded as a JSON object
+    #[prost(string, tag = "6")]
+    pub attributes: ::prost::alloc::string::String,
+    /// inactive resources could not be reserved
+    #[prost(bool, tag = "7")]
+    pub active: bool,
+    /// turnaround time before and after each reservation, in which the resource could not
+    /// be reserved by others
+    #[prost(int32, tag = "8")]
+    pub buffer_before_minutes: i32,
+    #[prost(int32, tag = "9")]
+    pub buffer_after_minutes: i32,
+    /// how long a pending reservation holds its slot, 0 to use the global default
+    #[prost(int32, tag = "10")]
+    pub hold_ttl_seconds: i32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateResourceRequest {
+    #[prost(message, optional, tag = "1")]
+    pub resource: ::core::option::Option<Resource>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateResourceResponse {
+    #[prost(message, optional, tag = "1")]
+    pub resource: ::core::option::Option<Resource>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateResourceRequest {
+    #[prost(message, optional, tag = "1")]
+    pub resource: ::core::option::Option<Resource>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateResourceResponse {
+    #[prost(message, optional, tag = "1")]
+    pub resource: ::core::option::Option<Resource>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetResourceRequest {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetResourceResponse {
+    #[prost(message, optional, tag = "1")]
+    pub resource: ::core::option::Option<Resource>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteResourceRequest {
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteResourceResponse {
+    #[prost(message, optional, tag = "1")]
+    pub resource: ::core::option::Option<Resource>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListResourcesRequest {
+    /// resource type to list, If empty, list resources of all types
+    #[prost(string, tag = "1")]
+    pub resource_type: ::prost::alloc::string::String,
+    /// include inactive resources
+    #[prost(bool, tag = "2")]
+    pub include_inactive: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListResourcesResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub resources: ::prost::alloc::vec::Vec<Resource>,
+}
+/// booking policies for a resource, or for all resources of a type.
+/// rules of a resource take precedence over rules of its type. 0 means no limit
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BookingRules {
+    /// set either resource_id or resource_type
+    #[prost(string, tag = "1")]
+    pub resource_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub resource_type: ::prost::alloc::string::String,
+    #[prost(int32, tag = "3")]
+    pub min_duration_minutes: i32,
+    #[prost(int32, tag = "4")]
+    pub max_duration_minutes: i32,
+    /// how far ahead a reservation could be made
+    #[prost(int32, tag = "5")]
+    pub max_lead_time_minutes: i32,
+    /// how long before its start a reservation must be made
+    #[prost(int32, tag = "6")]
+    pub min_notice_minutes: i32,
+    /// hours of operation in the resource timezone, as minutes from midnight.
+    /// If open_minute equals close_minute, the resource is always open
+    #[prost(int32, tag = "7")]
+    pub open_minute: i32,
+    #[prost(int32, tag = "8")]
+    pub close_minute: i32,
+    /// reservations must start at a multiple of slot_minutes from midnight in the resource timezone
+    #[prost(int32, tag = "9")]
+    pub slot_minutes: i32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetBookingRulesRequest {
+    #[prost(message, optional, tag = "1")]
+    pub rules: ::core::option::Option<BookingRules>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetBookingRulesResponse {
+    #[prost(message, optional, tag = "1")]
+    pub rules: ::core::option::Option<BookingRules>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetBookingRulesRequest {
+    /// set either resource_id or resource_type
+    #[prost(string, tag = "1")]
+    pub resource_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub resource_type: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetBookingRulesResponse {
+    #[prost(message, optional, tag = "1")]
+    pub rules: ::core::option::Option<BookingRules>,
+}
+/// per user quotas on reservations of a resource type. 0 means no limit
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Quota {
+    #[prost(string, tag = "1")]
+    pub resource_type: ::prost::alloc::string::String,
+    /// pending or confirmed reservations which are not finished yet
+    #[prost(int32, tag = "2")]
+    pub max_active: i32,
+    /// booked hours in a week (Monday to Sunday, UTC)
+    #[prost(int32, tag = "3")]
+    pub max_hours_per_week: i32,
+    /// reservations overlapping with each other
+    #[prost(int32, tag = "4")]
+    pub max_concurrent: i32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetQuotaRequest {
+    #[prost(message, optional, tag = "1")]
+    pub quota: ::core::option::Option<Quota>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetQuotaResponse {
+    #[prost(message, optional, tag = "1")]
+    pub quota: ::core::option::Option<Quota>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetQuotaRequest {
+    #[prost(string, tag = "1")]
+    pub user_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub resource_type: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetQuotaResponse {
+    #[prost(message, optional, tag = "1")]
+    pub quota: ::core::option::Option<Quota>,
+    /// remaining active reservations of the user, absent if not limited
+    #[prost(int32, optional, tag = "2")]
+    pub remaining_active: ::core::option::Option<i32>,
+    /// remaining hours of the user in the current week, absent if not limited
+    #[prost(double, optional, tag = "3")]
+    pub remaining_hours: ::core::option::Option<f64>,
+}
+/// a user waiting for a fully booked window, promoted to a pending reservation once the
+/// window is available
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WaitlistEntry {
+    #[prost(int64, tag = "1")]
+    pub id: i64,
+    #[prost(string, tag = "2")]
+    pub user_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub resource_id: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "4")]
+    pub start: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(message, optional, tag = "5")]
+    pub end: ::core::option::Option<::prost_types::Timestamp>,
+    /// units of the resource to reserve, default to 1
+    #[prost(int32, tag = "6")]
+    pub quantity: i32,
+    /// note of the reservation once promoted
+    #[prost(string, tag = "7")]
+    pub note: ::prost::alloc::string::String,
+    /// id of the promoted reservation, 0 if still waiting
+    #[prost(int64, tag = "8")]
+    pub reservation_id: i64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct JoinWaitlistRequest {
+    #[prost(message, optional, tag = "1")]
+    pub entry: ::core::option::Option<WaitlistEntry>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct JoinWaitlistResponse {
+    #[prost(message, optional, tag = "1")]
+    pub entry: ::core::option::Option<WaitlistEntry>,
+}
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListenRequest {}
@@ -178,6 +506,66 @@ pub struct ListenResponse {
     #[prost(message, optional, tag = "2")]
     pub reservation: ::core::option::Option<Reservation>,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StatsQuery {
+    /// if empty, all resources / users, and pending and confirmed reservations
+    #[prost(string, repeated, tag = "1")]
+    pub resource_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, repeated, tag = "2")]
+    pub user_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(enumeration = "ReservationStatus", repeated, tag = "3")]
+    pub statuses: ::prost::alloc::vec::Vec<i32>,
+    /// time range to aggregate, both are required
+    #[prost(message, optional, tag = "4")]
+    pub start: ::core::option::Option<::prost_types::Timestamp>,
+    #[prost(message, optional, tag = "5")]
+    pub end: ::core::option::Option<::prost_types::Timestamp>,
+    /// buckets are aligned in the timezone of each resource
+    #[prost(enumeration = "StatsInterval", tag = "6")]
+    pub interval: i32,
+}
+/// utilization of a resource in a time bucket, only buckets with reservations are returned
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ResourceStats {
+    #[prost(string, tag = "1")]
+    pub resource_id: ::prost::alloc::string::String,
+    /// start of the bucket
+    #[prost(message, optional, tag = "2")]
+    pub bucket: ::core::option::Option<::prost_types::Timestamp>,
+    /// reservations overlapping the bucket
+    #[prost(int64, tag = "3")]
+    pub reservations: i64,
+    /// reserved hours within the bucket and the time range, multiplied by the quantity
+    #[prost(double, tag = "4")]
+    pub booked_hours: f64,
+    /// max units reserved at the same time
+    #[prost(int32, tag = "5")]
+    pub peak_quantity: i32,
+    /// peak_quantity divided by the capacity of the resource
+    #[prost(double, tag = "6")]
+    pub peak_occupancy: f64,
+    /// share of the started bookings (pending or confirmed) which were never confirmed,
+    /// 0 if none started
+    #[prost(double, tag = "7")]
+    pub no_show_rate: f64,
+    /// average hours between making a reservation and its start
+    #[prost(double, tag = "8")]
+    pub avg_lead_hours: f64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetStatsRequest {
+    #[prost(message, optional, tag = "1")]
+    pub query: ::core::option::Option<StatsQuery>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetStatsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub stats: ::prost::alloc::vec::Vec<ResourceStats>,
+}
 #[derive(
     sqlx::Type, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration,
 )]
@@ -187,6 +575,8 @@ pub enum ReservationStatus {
     Pending = 1,
     Confirmed = 2,
     Blocked = 3,
+    /// pending reservation not confirmed in time
+    Expired = 4,
 }
 impl ReservationStatus {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -199,6 +589,7 @@ impl ReservationStatus {
             ReservationStatus::Pending => "RESERVATION_STATUS_PENDING",
             ReservationStatus::Confirmed => "RESERVATION_STATUS_CONFIRMED",
             ReservationStatus::Blocked => "RESERVATION_STATUS_BLOCKED",
+            ReservationStatus::Expired => "RESERVATION_STATUS_EXPIRED",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -208,6 +599,83 @@ impl ReservationStatus {
             "RESERVATION_STATUS_PENDING" => Some(Self::Pending),
             "RESERVATION_STATUS_CONFIRMED" => Some(Self::Confirmed),
             "RESERVATION_STATUS_BLOCKED" => Some(Self::Blocked),
+            "RESERVATION_STATUS_EXPIRED" => Some(Self::Expired),
+            _ => None,
+        }
+    }
+}
+/// keys to sort filter results by, ties are broken by id
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ReservationSortBy {
+    Id = 0,
+    Start = 1,
+    End = 2,
+    UserId = 3,
+    ResourceId = 4,
+    CreatedAt = 5,
+}
+impl ReservationSortBy {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ReservationSortBy::Id => "RESERVATION_SORT_BY_ID",
+            ReservationSortBy::Start => "RESERVATION_SORT_BY_START",
+            ReservationSortBy::End => "RESERVATION_SORT_BY_END",
+            ReservationSortBy::UserId => "RESERVATION_SORT_BY_USER_ID",
+            ReservationSortBy::ResourceId => "RESERVATION_SORT_BY_RESOURCE_ID",
+            ReservationSortBy::CreatedAt => "RESERVATION_SORT_BY_CREATED_AT",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "RESERVATION_SORT_BY_ID" => Some(Self::Id),
+            "RESERVATION_SORT_BY_START" => Some(Self::Start),
+            "RESERVATION_SORT_BY_END" => Some(Self::End),
+            "RESERVATION_SORT_BY_USER_ID" => Some(Self::UserId),
+            "RESERVATION_SORT_BY_RESOURCE_ID" => Some(Self::ResourceId),
+            "RESERVATION_SORT_BY_CREATED_AT" => Some(Self::CreatedAt),
+            _ => None,
+        }
+    }
+}
+/// how the time range of a query matches the reservations, all ranges are [start, end)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ReservationMatchMode {
+    /// reservations within the range
+    ContainedIn = 0,
+    /// reservations overlapping the range
+    Overlaps = 1,
+    /// reservations covering all of the range
+    Contains = 2,
+    /// reservations starting within the range
+    StartsWithin = 3,
+}
+impl ReservationMatchMode {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ReservationMatchMode::ContainedIn => "RESERVATION_MATCH_MODE_CONTAINED_IN",
+            ReservationMatchMode::Overlaps => "RESERVATION_MATCH_MODE_OVERLAPS",
+            ReservationMatchMode::Contains => "RESERVATION_MATCH_MODE_CONTAINS",
+            ReservationMatchMode::StartsWithin => "RESERVATION_MATCH_MODE_STARTS_WITHIN",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "RESERVATION_MATCH_MODE_CONTAINED_IN" => Some(Self::ContainedIn),
+            "RESERVATION_MATCH_MODE_OVERLAPS" => Some(Self::Overlaps),
+            "RESERVATION_MATCH_MODE_CONTAINS" => Some(Self::Contains),
+            "RESERVATION_MATCH_MODE_STARTS_WITHIN" => Some(Self::StartsWithin),
             _ => None,
         }
     }
@@ -244,6 +712,37 @@ impl ReservationUpdateType {
         }
     }
 }
+/// size of the time buckets of the statistics
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum StatsInterval {
+    Day = 0,
+    Hour = 1,
+    /// weeks start on Monday
+    Week = 2,
+}
+impl StatsInterval {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            StatsInterval::Day => "STATS_INTERVAL_DAY",
+            StatsInterval::Hour => "STATS_INTERVAL_HOUR",
+            StatsInterval::Week => "STATS_INTERVAL_WEEK",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "STATS_INTERVAL_DAY" => Some(Self::Day),
+            "STATS_INTERVAL_HOUR" => Some(Self::Hour),
+            "STATS_INTERVAL_WEEK" => Some(Self::Week),
+            _ => None,
+        }
+    }
+}
 /// Generated client implementations.
 pub mod reservation_service_client {
     #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
@@ -438,56 +937,283 @@ pub mod reservation_service_client {
                 .server_streaming(request.into_request(), path, codec)
                 .await
         }
-    }
-}
-/// Generated server implementations.
-pub mod reservation_service_server {
-    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
-    use tonic::codegen::*;
-    /// Generated trait containing gRPC methods that should be implemented for use with ReservationServiceServer.
-    #[async_trait]
-    pub trait ReservationService: Send + Sync + 'static {
-        async fn reserve(
-            &self,
-            request: tonic::Request<super::ReserveRequest>,
-        ) -> Result<tonic::Response<super::ReserveResponse>, tonic::Status>;
-        async fn confirm(
-            &self,
-            request: tonic::Request<super::ConfirmRequest>,
-        ) -> Result<tonic::Response<super::ConfirmResponse>, tonic::Status>;
-        async fn update(
-            &self,
-            request: tonic::Request<super::UpdateRequest>,
-        ) -> Result<tonic::Response<super::UpdateResponse>, tonic::Status>;
-        async fn cancel(
-            &self,
-            request: tonic::Request<super::CancelRequest>,
-        ) -> Result<tonic::Response<super::CancelResponse>, tonic::Status>;
-        async fn get(
-            &self,
-            request: tonic::Request<super::GetRequest>,
-        ) -> Result<tonic::Response<super::GetResponse>, tonic::Status>;
-        /// Server streaming response type for the query method.
-        type queryStream: futures_core::Stream<Item = Result<super::Reservation, tonic::Status>>
-            + Send
-            + 'static;
-        async fn query(
-            &self,
-            request: tonic::Request<super::QueryRequest>,
-        ) -> Result<tonic::Response<Self::queryStream>, tonic::Status>;
-        async fn filter(
-            &self,
-            request: tonic::Request<super::FilterRequest>,
-        ) -> Result<tonic::Response<super::FilterResponse>, tonic::Status>;
-        /// Server streaming response type for the listen method.
-        type listenStream: futures_core::Stream<Item = Result<super::Reservation, tonic::Status>>
-            + Send
-            + 'static;
-        /// another system could monitor newly added/confirmed/canceled reservation
-        async fn listen(
-            &self,
-            request: tonic::Request<super::ListenRequest>,
+        /// resource catalog
+        pub async fn create_resource(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateResourceRequest>,
+        ) -> Result<tonic::Response<super::CreateResourceResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/create_resource",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn update_resource(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdateResourceRequest>,
+        ) -> Result<tonic::Response<super::UpdateResourceResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/update_resource",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn get_resource(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetResourceRequest>,
+        ) -> Result<tonic::Response<super::GetResourceResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/get_resource",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn delete_resource(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DeleteResourceRequest>,
+        ) -> Result<tonic::Response<super::DeleteResourceResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/delete_resource",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn list_resources(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListResourcesRequest>,
+        ) -> Result<tonic::Response<super::ListResourcesResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/list_resources",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// booking rules
+        pub async fn set_booking_rules(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SetBookingRulesRequest>,
+        ) -> Result<tonic::Response<super::SetBookingRulesResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/set_booking_rules",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn get_booking_rules(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetBookingRulesRequest>,
+        ) -> Result<tonic::Response<super::GetBookingRulesResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/get_booking_rules",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// quotas
+        pub async fn set_quota(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SetQuotaRequest>,
+        ) -> Result<tonic::Response<super::SetQuotaResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/set_quota");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        pub async fn get_quota(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetQuotaRequest>,
+        ) -> Result<tonic::Response<super::GetQuotaResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/get_quota");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// queue for a window which is fully booked, promoted right away if it is free
+        pub async fn join_waitlist(
+            &mut self,
+            request: impl tonic::IntoRequest<super::JoinWaitlistRequest>,
+        ) -> Result<tonic::Response<super::JoinWaitlistResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/reservation.ReservationService/join_waitlist",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// utilization of resources
+        pub async fn get_stats(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetStatsRequest>,
+        ) -> Result<tonic::Response<super::GetStatsResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/reservation.ReservationService/get_stats");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+    }
+}
+/// Generated server implementations.
+pub mod reservation_service_server {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with ReservationServiceServer.
+    #[async_trait]
+    pub trait ReservationService: Send + Sync + 'static {
+        async fn reserve(
+            &self,
+            request: tonic::Request<super::ReserveRequest>,
+        ) -> Result<tonic::Response<super::ReserveResponse>, tonic::Status>;
+        async fn confirm(
+            &self,
+            request: tonic::Request<super::ConfirmRequest>,
+        ) -> Result<tonic::Response<super::ConfirmResponse>, tonic::Status>;
+        async fn update(
+            &self,
+            request: tonic::Request<super::UpdateRequest>,
+        ) -> Result<tonic::Response<super::UpdateResponse>, tonic::Status>;
+        async fn cancel(
+            &self,
+            request: tonic::Request<super::CancelRequest>,
+        ) -> Result<tonic::Response<super::CancelResponse>, tonic::Status>;
+        async fn get(
+            &self,
+            request: tonic::Request<super::GetRequest>,
+        ) -> Result<tonic::Response<super::GetResponse>, tonic::Status>;
+        /// Server streaming response type for the query method.
+        type queryStream: futures_core::Stream<Item = Result<super::Reservation, tonic::Status>>
+            + Send
+            + 'static;
+        async fn query(
+            &self,
+            request: tonic::Request<super::QueryRequest>,
+        ) -> Result<tonic::Response<Self::queryStream>, tonic::Status>;
+        async fn filter(
+            &self,
+            request: tonic::Request<super::FilterRequest>,
+        ) -> Result<tonic::Response<super::FilterResponse>, tonic::Status>;
+        /// Server streaming response type for the listen method.
+        type listenStream: futures_core::Stream<Item = Result<super::Reservation, tonic::Status>>
+            + Send
+            + 'static;
+        /// another system could monitor newly added/confirmed/canceled reservation
+        async fn listen(
+            &self,
+            request: tonic::Request<super::ListenRequest>,
         ) -> Result<tonic::Response<Self::listenStream>, tonic::Status>;
+        /// resource catalog
+        async fn create_resource(
+            &self,
+            request: tonic::Request<super::CreateResourceRequest>,
+        ) -> Result<tonic::Response<super::CreateResourceResponse>, tonic::Status>;
+        async fn update_resource(
+            &self,
+            request: tonic::Request<super::UpdateResourceRequest>,
+        ) -> Result<tonic::Response<super::UpdateResourceResponse>, tonic::Status>;
+        async fn get_resource(
+            &self,
+            request: tonic::Request<super::GetResourceRequest>,
+        ) -> Result<tonic::Response<super::GetResourceResponse>, tonic::Status>;
+        async fn delete_resource(
+            &self,
+            request: tonic::Request<super::DeleteResourceRequest>,
+        ) -> Result<tonic::Response<super::DeleteResourceResponse>, tonic::Status>;
+        async fn list_resources(
+            &self,
+            request: tonic::Request<super::ListResourcesRequest>,
+        ) -> Result<tonic::Response<super::ListResourcesResponse>, tonic::Status>;
+        /// booking rules
+        async fn set_booking_rules(
+            &self,
+            request: tonic::Request<super::SetBookingRulesRequest>,
+        ) -> Result<tonic::Response<super::SetBookingRulesResponse>, tonic::Status>;
+        async fn get_booking_rules(
+            &self,
+            request: tonic::Request<super::GetBookingRulesRequest>,
+        ) -> Result<tonic::Response<super::GetBookingRulesResponse>, tonic::Status>;
+        /// quotas
+        async fn set_quota(
+            &self,
+            request: tonic::Request<super::SetQuotaRequest>,
+        ) -> Result<tonic::Response<super::SetQuotaResponse>, tonic::Status>;
+        async fn get_quota(
+            &self,
+            request: tonic::Request<super::GetQuotaRequest>,
+        ) -> Result<tonic::Response<super::GetQuotaResponse>, tonic::Status>;
+        /// queue for a window which is fully booked, promoted right away if it is free
+        async fn join_waitlist(
+            &self,
+            request: tonic::Request<super::JoinWaitlistRequest>,
+        ) -> Result<tonic::Response<super::JoinWaitlistResponse>, tonic::Status>;
+        /// utilization of resources
+        async fn get_stats(
+            &self,
+            request: tonic::Request<super::GetStatsRequest>,
+        ) -> Result<tonic::Response<super::GetStatsResponse>, tonic::Status>;
     }
     #[derive(Debug)]
     pub struct ReservationServiceServer<T: ReservationService> {
@@ -799,6 +1525,377 @@ pub mod reservation_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/reservation.ReservationService/create_resource" => {
+                    #[allow(non_camel_case_types)]
+                    struct create_resourceSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::CreateResourceRequest>
+                        for create_resourceSvc<T>
+                    {
+                        type Response = super::CreateResourceResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateResourceRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).create_resource(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = create_resourceSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/update_resource" => {
+                    #[allow(non_camel_case_types)]
+                    struct update_resourceSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::UpdateResourceRequest>
+                        for update_resourceSvc<T>
+                    {
+                        type Response = super::UpdateResourceResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UpdateResourceRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).update_resource(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = update_resourceSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/get_resource" => {
+                    #[allow(non_camel_case_types)]
+                    struct get_resourceSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::GetResourceRequest>
+                        for get_resourceSvc<T>
+                    {
+                        type Response = super::GetResourceResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetResourceRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).get_resource(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = get_resourceSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/delete_resource" => {
+                    #[allow(non_camel_case_types)]
+                    struct delete_resourceSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::DeleteResourceRequest>
+                        for delete_resourceSvc<T>
+                    {
+                        type Response = super::DeleteResourceResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DeleteResourceRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).delete_resource(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = delete_resourceSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/list_resources" => {
+                    #[allow(non_camel_case_types)]
+                    struct list_resourcesSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::ListResourcesRequest>
+                        for list_resourcesSvc<T>
+                    {
+                        type Response = super::ListResourcesResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListResourcesRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).list_resources(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = list_resourcesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/set_booking_rules" => {
+                    #[allow(non_camel_case_types)]
+                    struct set_booking_rulesSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::SetBookingRulesRequest>
+                        for set_booking_rulesSvc<T>
+                    {
+                        type Response = super::SetBookingRulesResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SetBookingRulesRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).set_booking_rules(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = set_booking_rulesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/get_booking_rules" => {
+                    #[allow(non_camel_case_types)]
+                    struct get_booking_rulesSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::GetBookingRulesRequest>
+                        for get_booking_rulesSvc<T>
+                    {
+                        type Response = super::GetBookingRulesResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetBookingRulesRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).get_booking_rules(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = get_booking_rulesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/set_quota" => {
+                    #[allow(non_camel_case_types)]
+                    struct set_quotaSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService> tonic::server::UnaryService<super::SetQuotaRequest>
+                        for set_quotaSvc<T>
+                    {
+                        type Response = super::SetQuotaResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SetQuotaRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).set_quota(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = set_quotaSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/get_quota" => {
+                    #[allow(non_camel_case_types)]
+                    struct get_quotaSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService> tonic::server::UnaryService<super::GetQuotaRequest>
+                        for get_quotaSvc<T>
+                    {
+                        type Response = super::GetQuotaResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetQuotaRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).get_quota(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = get_quotaSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/join_waitlist" => {
+                    #[allow(non_camel_case_types)]
+                    struct join_waitlistSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService>
+                        tonic::server::UnaryService<super::JoinWaitlistRequest>
+                        for join_waitlistSvc<T>
+                    {
+                        type Response = super::JoinWaitlistResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::JoinWaitlistRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).join_waitlist(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = join_waitlistSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/reservation.ReservationService/get_stats" => {
+                    #[allow(non_camel_case_types)]
+                    struct get_statsSvc<T: ReservationService>(pub Arc<T>);
+                    impl<T: ReservationService> tonic::server::UnaryService<super::GetStatsRequest>
+                        for get_statsSvc<T>
+                    {
+                        type Response = super::GetStatsResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetStatsRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).get_stats(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = get_statsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => Box::pin(async move {
                     Ok(http::Response::builder()
                         .status(200)
@@ -0,0 +1,362 @@
+use chrono::{DateTime, Duration, Timelike, Utc};
+use chrono_tz::Tz;
+use sqlx::{postgres::PgRow, FromRow, Row};
+
+use crate::{BookingRules, Error, Validator};
+
+const MINUTES_PER_DAY: i32 = 24 * 60;
+
+impl BookingRules {
+    pub fn for_resource(rid: impl Into<String>) -> Self {
+        Self {
+            resource_id: rid.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn for_resource_type(rtype: impl Into<String>) -> Self {
+        Self {
+            resource_type: rtype.into(),
+            ..Default::default()
+        }
+    }
+
+    /// check a reservation window against the rules, `tz` is the timezone of the resource
+    pub fn check(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        tz: Tz,
+        now: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        let duration = (end - start).num_minutes();
+        if self.min_duration_minutes > 0 && duration < self.min_duration_minutes as i64 {
+            return Err(Error::DurationTooShort(duration, self.min_duration_minutes));
+        }
+
+        if self.max_duration_minutes > 0 && duration > self.max_duration_minutes as i64 {
+            return Err(Error::DurationTooLong(duration, self.max_duration_minutes));
+        }
+
+        let lead_time = (start - now).num_minutes();
+        if self.max_lead_time_minutes > 0 && lead_time > self.max_lead_time_minutes as i64 {
+            return Err(Error::TooFarInAdvance(
+                lead_time,
+                self.max_lead_time_minutes,
+            ));
+        }
+
+        if self.min_notice_minutes > 0 && lead_time < self.min_notice_minutes as i64 {
+            return Err(Error::InsufficientNotice(
+                lead_time,
+                self.min_notice_minutes,
+            ));
+        }
+
+        let local_start = start.with_timezone(&tz);
+        let local_end = end.with_timezone(&tz);
+        let start_minute = minute_of_day(&local_start);
+
+        if self.open_minute != self.close_minute {
+            // a reservation ends at midnight belongs to the day before
+            let end_minute = if local_end.date_naive() == local_start.date_naive() {
+                Some(minute_of_day(&local_end))
+            } else if (local_end - Duration::nanoseconds(1)).date_naive()
+                == local_start.date_naive()
+            {
+                Some(MINUTES_PER_DAY)
+            } else {
+                None
+            };
+
+            let inside = match end_minute {
+                Some(end_minute) => {
+                    start_minute >= self.open_minute && end_minute <= self.close_minute
+                }
+                None => false,
+            };
+
+            if !inside {
+                return Err(Error::OutsideOperatingHours(
+                    format!("{} - {}", local_start.to_rfc3339(), local_end.to_rfc3339()),
+                    format!(
+                        "{} - {}",
+                        format_minute(self.open_minute),
+                        format_minute(self.close_minute)
+                    ),
+                ));
+            }
+        }
+
+        if self.slot_minutes > 0
+            && (start_minute % self.slot_minutes != 0
+                || local_start.second() != 0
+                || local_start.nanosecond() != 0)
+        {
+            return Err(Error::MisalignedStart(
+                local_start.to_rfc3339(),
+                self.slot_minutes,
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Validator for BookingRules {
+    fn validate(&self) -> Result<(), Error> {
+        if self.resource_id.is_empty() == self.resource_type.is_empty() {
+            return Err(Error::InvalidBookingRules(
+                "either resource_id or resource_type should be set".into(),
+            ));
+        }
+
+        let limits = [
+            self.min_duration_minutes,
+            self.max_duration_minutes,
+            self.max_lead_time_minutes,
+            self.min_notice_minutes,
+            self.slot_minutes,
+        ];
+        if limits.iter().any(|v| *v < 0) {
+            return Err(Error::InvalidBookingRules(
+                "limits should not be negative".into(),
+            ));
+        }
+
+        if self.max_duration_minutes > 0 && self.min_duration_minutes > self.max_duration_minutes {
+            return Err(Error::InvalidBookingRules(
+                "min duration is longer than max duration".into(),
+            ));
+        }
+
+        if self.open_minute < 0
+            || self.close_minute > MINUTES_PER_DAY
+            || self.open_minute > self.close_minute
+        {
+            return Err(Error::InvalidBookingRules(format!(
+                "invalid hours of operation {} - {}",
+                self.open_minute, self.close_minute
+            )));
+        }
+
+        if self.slot_minutes > MINUTES_PER_DAY {
+            return Err(Error::InvalidBookingRules(format!(
+                "slot of {} minutes is longer than a day",
+                self.slot_minutes
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl FromRow<'_, PgRow> for BookingRules {
+    fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
+        let resource_id: Option<String> = row.get("resource_id");
+        let resource_type: Option<String> = row.get("resource_type");
+
+        Ok(Self {
+            resource_id: resource_id.unwrap_or_default(),
+            resource_type: resource_type.unwrap_or_default(),
+            min_duration_minutes: row.get("min_duration_minutes"),
+            max_duration_minutes: row.get("max_duration_minutes"),
+            max_lead_time_minutes: row.get("max_lead_time_minutes"),
+            min_notice_minutes: row.get("min_notice_minutes"),
+            open_minute: row.get("open_minute"),
+            close_minute: row.get("close_minute"),
+            slot_minutes: row.get("slot_minutes"),
+        })
+    }
+}
+
+fn minute_of_day<T: Timelike>(t: &T) -> i32 {
+    (t.hour() * 60 + t.minute()) as i32
+}
+
+fn format_minute(minute: i32) -> String {
+    format!("{:02}:{:02}", minute / 60, minute % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        s.parse::<DateTime<chrono::FixedOffset>>()
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn now() -> DateTime<Utc> {
+        utc("2023-01-02T08:00:00+00:00")
+    }
+
+    #[test]
+    fn duration_rules_should_work() {
+        let rules = BookingRules {
+            min_duration_minutes: 30,
+            max_duration_minutes: 120,
+            ..BookingRules::for_resource("room 518")
+        };
+
+        let start = utc("2023-01-03T10:00:00+00:00");
+        assert_eq!(
+            rules.check(start, utc("2023-01-03T10:15:00+00:00"), Tz::UTC, now()),
+            Err(Error::DurationTooShort(15, 30))
+        );
+        assert_eq!(
+            rules.check(start, utc("2023-01-03T13:00:00+00:00"), Tz::UTC, now()),
+            Err(Error::DurationTooLong(180, 120))
+        );
+        assert!(rules
+            .check(start, utc("2023-01-03T11:00:00+00:00"), Tz::UTC, now())
+            .is_ok());
+    }
+
+    #[test]
+    fn lead_time_rules_should_work() {
+        let rules = BookingRules {
+            max_lead_time_minutes: 7 * 24 * 60,
+            min_notice_minutes: 60,
+            ..BookingRules::for_resource_type("room")
+        };
+
+        assert_eq!(
+            rules.check(
+                utc("2023-01-10T10:00:00+00:00"),
+                utc("2023-01-10T11:00:00+00:00"),
+                Tz::UTC,
+                now()
+            ),
+            Err(Error::TooFarInAdvance(11640, 10080))
+        );
+        assert_eq!(
+            rules.check(
+                utc("2023-01-02T08:30:00+00:00"),
+                utc("2023-01-02T09:00:00+00:00"),
+                Tz::UTC,
+                now()
+            ),
+            Err(Error::InsufficientNotice(30, 60))
+        );
+        assert!(rules
+            .check(
+                utc("2023-01-02T09:00:00+00:00"),
+                utc("2023-01-02T10:00:00+00:00"),
+                Tz::UTC,
+                now()
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn operating_hours_rules_should_use_resource_timezone() {
+        // 08:00 - 18:00 in Los Angeles, which is 16:00 - 02:00 UTC in winter
+        let rules = BookingRules {
+            open_minute: 8 * 60,
+            close_minute: 18 * 60,
+            ..BookingRules::for_resource("room 518")
+        };
+        let tz: Tz = "America/Los_Angeles".parse().unwrap();
+
+        assert!(rules
+            .check(
+                utc("2023-01-03T16:00:00+00:00"),
+                utc("2023-01-04T02:00:00+00:00"),
+                tz,
+                now()
+            )
+            .is_ok());
+
+        let err = rules
+            .check(
+                utc("2023-01-03T15:00:00+00:00"),
+                utc("2023-01-03T17:00:00+00:00"),
+                tz,
+                now(),
+            )
+            .unwrap_err();
+        assert_eq!(
+            err,
+            Error::OutsideOperatingHours(
+                "2023-01-03T07:00:00-08:00 - 2023-01-03T09:00:00-08:00".into(),
+                "08:00 - 18:00".into()
+            )
+        );
+
+        // spans two days
+        assert!(rules
+            .check(
+                utc("2023-01-03T16:00:00+00:00"),
+                utc("2023-01-04T17:00:00+00:00"),
+                tz,
+                now()
+            )
+            .is_err());
+
+        // reservations could end at midnight if the resource closes at midnight
+        let rules = BookingRules {
+            open_minute: 8 * 60,
+            close_minute: 24 * 60,
+            ..BookingRules::for_resource("room 518")
+        };
+        assert!(rules
+            .check(
+                utc("2023-01-03T20:00:00+00:00"),
+                utc("2023-01-04T00:00:00+00:00"),
+                Tz::UTC,
+                now()
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn slot_rules_should_work() {
+        let rules = BookingRules {
+            slot_minutes: 30,
+            ..BookingRules::for_resource("room 518")
+        };
+
+        assert_eq!(
+            rules.check(
+                utc("2023-01-03T10:15:00+00:00"),
+                utc("2023-01-03T11:00:00+00:00"),
+                Tz::UTC,
+                now()
+            ),
+            Err(Error::MisalignedStart(
+                "2023-01-03T10:15:00+00:00".into(),
+                30
+            ))
+        );
+        assert!(rules
+            .check(
+                utc("2023-01-03T10:30:00+00:00"),
+                utc("2023-01-03T11:00:00+00:00"),
+                Tz::UTC,
+                now()
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn booking_rules_validate_should_work() {
+        assert!(BookingRules::default().validate().is_err());
+        assert!(BookingRules::for_resource("room 518").validate().is_ok());
+
+        let rules = BookingRules {
+            min_duration_minutes: 60,
+            max_duration_minutes: 30,
+            ..BookingRules::for_resource("room 518")
+        };
+        assert!(rules.validate().is_err());
+
+        let rules = BookingRules {
+            open_minute: 18 * 60,
+            close_minute: 8 * 60,
+            ..BookingRules::for_resource("room 518")
+        };
+        assert!(rules.validate().is_err());
+    }
+}
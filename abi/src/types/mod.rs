@@ -1,16 +1,26 @@
+mod booking_rules;
 pub mod pager;
+mod quota;
 mod request;
 mod reservation;
 mod reservation_filter;
+mod reservation_match_mode;
 mod reservation_query;
+mod reservation_sort_by;
 mod reservation_status;
+mod resource;
+mod stats;
+mod waitlist;
 
 use chrono::{DateTime, Utc};
 use prost_types::Timestamp;
-use sqlx::postgres::types::PgRange;
+use sqlx::{postgres::types::PgRange, Postgres, QueryBuilder};
 use std::ops::Bound;
 
-use crate::{convert_to_utc_time, Error};
+use crate::{convert_to_utc_time, Error, ReservationStatus};
+
+pub use quota::QuotaUsage;
+pub use reservation_query::ResumeKey;
 
 pub fn validate_range(start: Option<&Timestamp>, end: Option<&Timestamp>) -> Result<(), Error> {
     if start.is_none() || end.is_none() {
@@ -37,12 +47,60 @@ pub fn get_timespan(start: Option<&Timestamp>, end: Option<&Timestamp>) -> PgRan
     }
 }
 
-pub fn get_user_resource_cond(user_id: &str, resource_id: &str) -> String {
-    match (user_id.is_empty(), resource_id.is_empty()) {
-        (true, true) => "TRUE".into(),
-        (true, false) => format!("resource_id = '{resource_id}'"),
-        (false, true) => format!("user_id = '{user_id}'"),
-        (false, false) => format!("user_id = '{user_id}' AND resource_id = '{resource_id}'"),
+pub fn get_time_string(ts: Option<&Timestamp>, start: bool) -> String {
+    match ts {
+        Some(ts) => convert_to_utc_time(ts).to_rfc3339(),
+        None => (if start { "-infinity" } else { "infinity" }).into(),
+    }
+}
+
+/// values of a single-value field and its repeated counterpart, the single value is
+/// ignored if empty
+pub fn merge_values(value: &str, values: &[String]) -> Vec<String> {
+    let mut merged: Vec<String> = values.iter().filter(|v| !v.is_empty()).cloned().collect();
+    if !value.is_empty() && !merged.iter().any(|v| v == value) {
+        merged.push(value.to_string());
+    }
+    merged
+}
+
+/// statuses of a single-value status field and its repeated counterpart, UNKNOWN is ignored
+pub fn merge_statuses(status: i32, statuses: &[i32]) -> Vec<ReservationStatus> {
+    let mut merged = vec![];
+    for s in statuses.iter().chain([&status]) {
+        match ReservationStatus::from_i32(*s) {
+            Some(ReservationStatus::Unknown) | None => {}
+            Some(s) if merged.contains(&s) => {}
+            Some(s) => merged.push(s),
+        }
+    }
+    merged
+}
+
+/// push `tenant_id = $n`, `status = ANY($n)` and the same conditions on the users and
+/// resources, each list is bound as an array. Empty user or resource lists match all
+pub fn push_reservation_conds(
+    builder: &mut QueryBuilder<'static, Postgres>,
+    tenant_id: &str,
+    statuses: &[ReservationStatus],
+    user_ids: Vec<String>,
+    resource_ids: Vec<String>,
+) {
+    let statuses: Vec<String> = statuses.iter().map(|s| s.to_string()).collect();
+    builder
+        .push("tenant_id = ")
+        .push_bind(tenant_id.to_string())
+        .push(" AND status = ANY(")
+        .push_bind(statuses)
+        .push("::rsvp.reservation_status[])");
+
+    for (column, values) in [("user_id", user_ids), ("resource_id", resource_ids)] {
+        if !values.is_empty() {
+            builder
+                .push(format_args!(" AND {column} = ANY("))
+                .push_bind(values)
+                .push(")");
+        }
     }
 }
 
@@ -1,17 +1,101 @@
 use std::collections::VecDeque;
 
-use crate::FilterPager;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::{Error, FilterPager};
+
+type HmacSha256 = Hmac<Sha256>;
 
 pub struct PageInfo {
     pub cursor: Option<i64>,
     pub page_size: i64,
     pub desc: bool,
+    /// signs the page tokens of the pager, no tokens are produced without it
+    pub signer: Option<PageSigner>,
 }
 
 pub struct Pager {
     pub prev: Option<i64>,
     pub next: Option<i64>,
     pub total: Option<i64>,
+    pub prev_token: Option<String>,
+    pub next_token: Option<String>,
+}
+
+/// position of a page, signed so that clients could neither forge it nor use it with
+/// another filter
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageToken {
+    /// the key results are sorted by
+    pub sort_by: String,
+    /// sort key and id of the row the page starts from
+    pub key: String,
+    pub cursor: i64,
+    pub desc: bool,
+    /// digest of the filter conditions the token is issued for
+    pub filter: String,
+}
+
+pub struct PageSigner {
+    pub secret: Vec<u8>,
+    pub sort_by: String,
+    pub filter: String,
+}
+
+impl PageToken {
+    pub fn encode(&self, secret: &[u8]) -> String {
+        let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap());
+        sign(payload, secret)
+    }
+
+    pub fn decode(token: &str, secret: &[u8]) -> Result<Self, Error> {
+        let payload = verify(token, secret, "page token")?;
+        URL_SAFE_NO_PAD
+            .decode(payload)
+            .ok()
+            .and_then(|payload| serde_json::from_slice(&payload).ok())
+            .ok_or_else(|| Error::InvalidCursor(format!("malformed page token {token}")))
+    }
+}
+
+/// `<payload>.<signature>`, the payload is signed with HMAC-SHA256
+pub(crate) fn sign(payload: String, secret: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any size");
+    mac.update(payload.as_bytes());
+    let signature = URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes());
+
+    format!("{payload}.{signature}")
+}
+
+/// payload of a token signed with the secret, `kind` names the token in the errors
+pub(crate) fn verify<'a>(token: &'a str, secret: &[u8], kind: &str) -> Result<&'a str, Error> {
+    let invalid = || Error::InvalidCursor(format!("malformed {kind} {token}"));
+
+    let (payload, signature) = token.split_once('.').ok_or_else(invalid)?;
+    let signature = URL_SAFE_NO_PAD.decode(signature).map_err(|_| invalid())?;
+
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any size");
+    mac.update(payload.as_bytes());
+    mac.verify_slice(&signature)
+        .map_err(|_| Error::InvalidCursor(format!("{kind} signature mismatch")))?;
+
+    Ok(payload)
+}
+
+impl PageSigner {
+    pub fn sign<T: Id>(&self, row: &T, desc: bool) -> String {
+        PageToken {
+            sort_by: self.sort_by.clone(),
+            key: row.sort_key(&self.sort_by),
+            cursor: row.id(),
+            desc,
+            filter: self.filter.clone(),
+        }
+        .encode(&self.secret)
+    }
 }
 
 impl From<Pager> for FilterPager {
@@ -20,16 +104,21 @@ impl From<Pager> for FilterPager {
             prev: value.prev,
             next: value.next,
             total: value.total,
+            prev_token: value.prev_token.unwrap_or_default(),
+            next_token: value.next_token.unwrap_or_default(),
         }
     }
 }
 
 impl From<&FilterPager> for Pager {
     fn from(value: &FilterPager) -> Self {
+        let token = |t: &String| (!t.is_empty()).then(|| t.clone());
         Self {
             prev: value.prev,
             next: value.next,
             total: value.total,
+            prev_token: token(&value.prev_token),
+            next_token: token(&value.next_token),
         }
     }
 }
@@ -42,30 +131,44 @@ pub trait Paginator: Sized {
 
 pub trait Id {
     fn id(&self) -> i64;
+
+    /// value of the sort key of the row, carried by page tokens
+    fn sort_key(&self, _sort_by: &str) -> String {
+        self.id().to_string()
+    }
 }
 
 impl Paginator for PageInfo {
     fn get_pager<T: Id>(&self, data: &mut VecDeque<T>) -> Pager {
+        let sign = |row: Option<&T>| {
+            self.signer
+                .as_ref()
+                .zip(row)
+                .map(|(signer, row)| signer.sign(row, self.desc))
+        };
+
         let has_prev = self.cursor.is_some();
-        let prev = if has_prev {
+        let (prev, prev_token) = if has_prev {
             data.pop_front();
-            data.front().map(|v| v.id())
+            (data.front().map(|v| v.id()), sign(data.front()))
         } else {
-            None
+            (None, None)
         };
 
         let has_next = data.len() as i64 > self.page_size;
-        let next = if has_next {
+        let (next, next_token) = if has_next {
             data.pop_back();
-            data.back().map(|v| v.id())
+            (data.back().map(|v| v.id()), sign(data.back()))
         } else {
-            None
+            (None, None)
         };
 
         Pager {
             prev,
             next,
             total: None,
+            prev_token,
+            next_token,
         }
     }
 
@@ -75,6 +178,7 @@ impl Paginator for PageInfo {
                 cursor: pager.prev,
                 page_size: self.page_size,
                 desc: self.desc,
+                signer: None,
             })
         } else {
             None
@@ -87,6 +191,7 @@ impl Paginator for PageInfo {
                 cursor: pager.next,
                 page_size: self.page_size,
                 desc: self.desc,
+                signer: None,
             })
         } else {
             None
@@ -127,6 +232,7 @@ mod tests {
             cursor: None,
             page_size: 10,
             desc: false,
+            signer: None,
         };
 
         // assume got 11 items from db
@@ -158,4 +264,66 @@ mod tests {
         let prev_page = page.prev_page(&pager);
         assert_eq!(prev_page.unwrap().cursor, Some(21));
     }
+
+    #[test]
+    fn paginator_should_sign_page_tokens() {
+        let page = PageInfo {
+            cursor: Some(10),
+            page_size: 10,
+            desc: false,
+            signer: Some(PageSigner {
+                secret: b"secret".to_vec(),
+                sort_by: "id".into(),
+                filter: "digest".into(),
+            }),
+        };
+
+        let mut items = pager_test_utils::generate_test_ids(10, 21);
+        let pager = page.get_pager(&mut items);
+
+        let token = PageToken::decode(pager.next_token.as_ref().unwrap(), b"secret").unwrap();
+        assert_eq!(
+            token,
+            PageToken {
+                sort_by: "id".into(),
+                key: "20".into(),
+                cursor: 20,
+                desc: false,
+                filter: "digest".into(),
+            }
+        );
+        let token = PageToken::decode(pager.prev_token.as_ref().unwrap(), b"secret").unwrap();
+        assert_eq!(token.cursor, 11);
+    }
+
+    #[test]
+    fn forged_page_token_should_be_rejected() {
+        let token = PageToken {
+            sort_by: "id".into(),
+            key: "20".into(),
+            cursor: 20,
+            desc: false,
+            filter: "digest".into(),
+        };
+        let encoded = token.encode(b"secret");
+
+        assert!(matches!(
+            PageToken::decode(&encoded, b"another secret"),
+            Err(Error::InvalidCursor(_))
+        ));
+
+        // the same signature with a different payload
+        let (_, signature) = encoded.split_once('.').unwrap();
+        let forged = PageToken { cursor: 1, ..token }.encode(b"another secret");
+        let (payload, _) = forged.split_once('.').unwrap();
+        assert!(matches!(
+            PageToken::decode(&format!("{payload}.{signature}"), b"secret"),
+            Err(Error::InvalidCursor(_))
+        ));
+
+        assert!(matches!(
+            PageToken::decode("not a token", b"secret"),
+            Err(Error::InvalidCursor(_))
+        ));
+    }
 }
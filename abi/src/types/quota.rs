@@ -0,0 +1,162 @@
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc};
+use sqlx::{postgres::types::PgRange, postgres::PgRow, FromRow, Row};
+use std::ops::Bound;
+
+use crate::{Error, Quota, Validator};
+
+/// reservations of a user on a resource type, counted for quota checks
+#[derive(Debug, Clone, Default, PartialEq, FromRow)]
+pub struct QuotaUsage {
+    /// pending or confirmed reservations which are not finished yet
+    pub active: i64,
+    /// booked hours in the week
+    pub week_hours: f64,
+    /// reservations overlapping with the requested window
+    pub concurrent: i64,
+}
+
+impl Quota {
+    pub fn new(resource_type: impl Into<String>) -> Self {
+        Self {
+            resource_type: resource_type.into(),
+            ..Default::default()
+        }
+    }
+
+    /// the week (Monday to Sunday, UTC) which contains the given time
+    pub fn week_of(t: DateTime<Utc>) -> PgRange<DateTime<Utc>> {
+        let date = t.date_naive() - Duration::days(t.weekday().num_days_from_monday() as i64);
+        let start = Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).unwrap());
+
+        PgRange {
+            start: Bound::Included(start),
+            end: Bound::Excluded(start + Duration::days(7)),
+        }
+    }
+
+    /// check if a new reservation of `hours` hours in the week fits in the quota
+    pub fn check(&self, user_id: &str, usage: &QuotaUsage, hours: f64) -> Result<(), Error> {
+        if self.max_active > 0 && usage.active >= self.max_active as i64 {
+            return Err(Error::QuotaExceeded(format!(
+                "user {} already has {} active {} reservations, the limit is {}",
+                user_id, usage.active, self.resource_type, self.max_active
+            )));
+        }
+
+        if self.max_hours_per_week > 0 && usage.week_hours + hours > self.max_hours_per_week as f64
+        {
+            return Err(Error::QuotaExceeded(format!(
+                "user {} has booked {:.1} hours of {} in the week, could not book {:.1} more within the limit {}",
+                user_id, usage.week_hours, self.resource_type, hours, self.max_hours_per_week
+            )));
+        }
+
+        if self.max_concurrent > 0 && usage.concurrent >= self.max_concurrent as i64 {
+            return Err(Error::QuotaExceeded(format!(
+                "user {} already has {} {} reservations at the same time, the limit is {}",
+                user_id, usage.concurrent, self.resource_type, self.max_concurrent
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub fn remaining_active(&self, usage: &QuotaUsage) -> Option<i32> {
+        (self.max_active > 0).then(|| (self.max_active as i64 - usage.active).max(0) as i32)
+    }
+
+    pub fn remaining_hours(&self, usage: &QuotaUsage) -> Option<f64> {
+        (self.max_hours_per_week > 0)
+            .then(|| (self.max_hours_per_week as f64 - usage.week_hours).max(0.0))
+    }
+}
+
+impl Validator for Quota {
+    fn validate(&self) -> Result<(), Error> {
+        if self.resource_type.is_empty() {
+            return Err(Error::InvalidQuota("resource_type should be set".into()));
+        }
+
+        if self.max_active < 0 || self.max_hours_per_week < 0 || self.max_concurrent < 0 {
+            return Err(Error::InvalidQuota("limits should not be negative".into()));
+        }
+
+        Ok(())
+    }
+}
+
+impl FromRow<'_, PgRow> for Quota {
+    fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            resource_type: row.get("resource_type"),
+            max_active: row.get("max_active"),
+            max_hours_per_week: row.get("max_hours_per_week"),
+            max_concurrent: row.get("max_concurrent"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn week_of_should_start_on_monday() {
+        // 2023-01-05 is a Thursday
+        let t = "2023-01-05T10:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let week = Quota::week_of(t);
+
+        assert_eq!(
+            week.start,
+            Bound::Included("2023-01-02T00:00:00Z".parse().unwrap())
+        );
+        assert_eq!(
+            week.end,
+            Bound::Excluded("2023-01-09T00:00:00Z".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn quota_check_should_work() {
+        let quota = Quota {
+            max_active: 2,
+            max_hours_per_week: 10,
+            max_concurrent: 1,
+            ..Quota::new("lab")
+        };
+
+        let usage = QuotaUsage {
+            active: 1,
+            week_hours: 8.0,
+            concurrent: 0,
+        };
+        assert!(quota.check("alice id", &usage, 2.0).is_ok());
+        assert_eq!(quota.remaining_active(&usage), Some(1));
+        assert_eq!(quota.remaining_hours(&usage), Some(2.0));
+
+        assert!(matches!(
+            quota.check("alice id", &usage, 3.0),
+            Err(Error::QuotaExceeded(_))
+        ));
+
+        let usage = QuotaUsage { active: 2, ..usage };
+        assert!(matches!(
+            quota.check("alice id", &usage, 1.0),
+            Err(Error::QuotaExceeded(_))
+        ));
+
+        let usage = QuotaUsage {
+            active: 0,
+            week_hours: 0.0,
+            concurrent: 1,
+        };
+        assert!(matches!(
+            quota.check("alice id", &usage, 1.0),
+            Err(Error::QuotaExceeded(_))
+        ));
+
+        // no limits
+        assert!(Quota::new("lab").check("alice id", &usage, 100.0).is_ok());
+        assert_eq!(Quota::new("lab").remaining_active(&usage), None);
+    }
+}
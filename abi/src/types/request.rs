@@ -1,6 +1,8 @@
 use crate::{
-    CancelRequest, ConfirmRequest, FilterRequest, GetRequest, QueryRequest, Reservation,
-    ReservationFilter, ReservationQuery, ReserveRequest, UpdateRequest,
+    BookingRules, CancelRequest, ConfirmRequest, CreateResourceRequest, DeleteResourceRequest,
+    FilterRequest, GetRequest, GetResourceRequest, JoinWaitlistRequest, QueryRequest, Quota,
+    Reservation, ReservationFilter, ReservationQuery, ReserveRequest, Resource,
+    SetBookingRulesRequest, SetQuotaRequest, UpdateRequest, UpdateResourceRequest, WaitlistEntry,
 };
 
 macro_rules! impl_new {
@@ -13,6 +15,13 @@ macro_rules! impl_new {
             }
         }
     };
+    ($name: ident, id: $type: ty) => {
+        impl $name {
+            pub fn new(id: impl Into<$type>) -> Self {
+                Self { id: id.into() }
+            }
+        }
+    };
     ($name: ident) => {
         impl $name {
             pub fn new(id: i64) -> Self {
@@ -28,6 +37,13 @@ impl_new!(QueryRequest, query, ReservationQuery);
 impl_new!(ConfirmRequest);
 impl_new!(GetRequest);
 impl_new!(CancelRequest);
+impl_new!(CreateResourceRequest, resource, Resource);
+impl_new!(UpdateResourceRequest, resource, Resource);
+impl_new!(GetResourceRequest, id: String);
+impl_new!(DeleteResourceRequest, id: String);
+impl_new!(SetBookingRulesRequest, rules, BookingRules);
+impl_new!(SetQuotaRequest, quota, Quota);
+impl_new!(JoinWaitlistRequest, entry, WaitlistEntry);
 
 impl UpdateRequest {
     pub fn new(id: i64, note: String) -> Self {
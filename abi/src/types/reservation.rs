@@ -1,13 +1,17 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use chrono::{DateTime, FixedOffset, Utc};
 use sqlx::{
     postgres::{types::PgRange, PgRow},
     FromRow, Row,
 };
 
+use prost_types::Timestamp;
 use std::{convert::Into, ops::Bound};
 
 use crate::{
-    convert_to_timestamp, pager::Id, Error, Reservation, ReservationStatus, RsvpStatus, Validator,
+    convert_to_timestamp, convert_to_utc_time,
+    pager::{self, Id},
+    Error, Reservation, ReservationStatus, RsvpStatus, Validator,
 };
 
 use super::{get_timespan, validate_range};
@@ -28,18 +32,50 @@ impl Reservation {
             end: Some(convert_to_timestamp(&end.with_timezone(&Utc))),
             note: note.into(),
             status: ReservationStatus::Pending as _,
+            quantity: 1,
+            expires_at: None,
+            created_at: None,
+            resume_token: String::new(),
         }
     }
 
+    pub fn with_quantity(mut self, quantity: i32) -> Self {
+        self.quantity = quantity;
+        self
+    }
+
     pub fn get_timespan(&self) -> PgRange<DateTime<Utc>> {
         get_timespan(self.start.as_ref(), self.end.as_ref())
     }
+
+    /// token to resume a query after this reservation, signed with the secret of the page
+    /// tokens
+    pub fn sign_resume_token(&self, secret: &[u8]) -> String {
+        let payload = URL_SAFE_NO_PAD.encode(format!("{} {}", self.sort_key("start"), self.id));
+        pager::sign(payload, secret)
+    }
 }
 
 impl Id for Reservation {
     fn id(&self) -> i64 {
         self.id
     }
+
+    fn sort_key(&self, sort_by: &str) -> String {
+        let time = |ts: Option<&Timestamp>| {
+            ts.map(|ts| convert_to_utc_time(ts).to_rfc3339())
+                .unwrap_or_default()
+        };
+
+        match sort_by {
+            "start" => time(self.start.as_ref()),
+            "end" => time(self.end.as_ref()),
+            "user_id" => self.user_id.clone(),
+            "resource_id" => self.resource_id.clone(),
+            "created_at" => time(self.created_at.as_ref()),
+            _ => self.id.to_string(),
+        }
+    }
 }
 
 impl Validator for Reservation {
@@ -52,6 +88,10 @@ impl Validator for Reservation {
             return Err(Error::InvalidResourceId(self.resource_id.clone()));
         }
 
+        if self.quantity < 0 {
+            return Err(Error::InvalidQuantity(self.quantity));
+        }
+
         validate_range(self.start.as_ref(), self.end.as_ref())?;
 
         Ok(())
@@ -69,6 +109,8 @@ impl FromRow<'_, PgRow> for Reservation {
         let end = range.end;
 
         let status: RsvpStatus = row.get("status");
+        let expires_at: Option<DateTime<Utc>> = row.get("expires_at");
+        let created_at: DateTime<Utc> = row.get("created_at");
 
         Ok(Self {
             id: rsvp_id,
@@ -78,6 +120,10 @@ impl FromRow<'_, PgRow> for Reservation {
             end: end.map(|e| convert_to_timestamp(&e)),
             note: row.get("note"),
             status: ReservationStatus::from(status) as _,
+            quantity: row.get("quantity"),
+            expires_at: expires_at.map(|t| convert_to_timestamp(&t)),
+            created_at: Some(convert_to_timestamp(&created_at)),
+            resume_token: String::new(),
         })
     }
 }
@@ -101,3 +147,33 @@ impl<T> From<PgRange<T>> for NaiveRange<T> {
         Self { start, end }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reservation_json_should_use_names_and_rfc3339() {
+        let rsvp = Reservation::new_pending(
+            "alice id",
+            "room 1",
+            "2022-12-25T15:00:00-0700".parse().unwrap(),
+            "2022-12-28T12:00:00-0700".parse().unwrap(),
+            "json",
+        );
+        let json = serde_json::to_value(&rsvp).unwrap();
+        assert_eq!(json["status"], "pending");
+        assert_eq!(json["start"], "2022-12-25T22:00:00Z");
+        assert_eq!(json["expires_at"], serde_json::Value::Null);
+        assert_eq!(serde_json::from_value::<Reservation>(json).unwrap(), rsvp);
+
+        // missing fields are defaults
+        let rsvp: Reservation = serde_json::from_str(
+            r#"{"user_id": "alice id", "start": "2022-12-25T15:00:00-07:00", "status": "confirmed"}"#,
+        )
+        .unwrap();
+        assert_eq!(rsvp.status, ReservationStatus::Confirmed as i32);
+        assert_eq!(rsvp.start.unwrap().seconds, 1672005600);
+        assert!(serde_json::from_str::<Reservation>(r#"{"status": "booked"}"#).is_err());
+    }
+}
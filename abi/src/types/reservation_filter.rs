@@ -1,10 +1,13 @@
 use std::collections::VecDeque;
 
+use sha2::{Digest, Sha256};
+use sqlx::{Postgres, QueryBuilder};
+
 use crate::{
-    get_user_resource_cond,
-    pager::{Id, PageInfo, Paginator},
-    Error, FilterPager, Normalize, ReservationFilter, ReservationFilterBuilder, ReservationStatus,
-    ToSql, Validator,
+    get_time_string, merge_statuses, merge_values,
+    pager::{Id, PageInfo, PageSigner, PageToken, Paginator},
+    push_reservation_conds, Error, FilterPager, Normalize, ReservationFilter,
+    ReservationFilterBuilder, ReservationSortBy, ReservationStatus, ToSql, Validator,
 };
 
 impl ReservationFilterBuilder {
@@ -27,11 +30,20 @@ impl Validator for ReservationFilter {
 
         if let Some(cursor) = self.cursor {
             if cursor < 0 {
-                return Err(Error::InvalidCursor(cursor));
+                return Err(Error::InvalidCursor(cursor.to_string()));
             }
         }
 
-        ReservationStatus::from_i32(self.status).ok_or(Error::InvalidStatus(self.status))?;
+        for status in self.statuses.iter().chain([&self.status]) {
+            ReservationStatus::from_i32(*status).ok_or(Error::InvalidStatus(*status))?;
+        }
+        ReservationSortBy::from_i32(self.sort_by).ok_or(Error::InvalidSortBy(self.sort_by))?;
+
+        if let (Some(start), Some(end)) = (self.start.as_ref(), self.end.as_ref()) {
+            if start.seconds >= end.seconds {
+                return Err(Error::InvalidTime);
+            }
+        }
 
         Ok(())
     }
@@ -40,49 +52,197 @@ impl Validator for ReservationFilter {
 impl Normalize for ReservationFilter {
     fn do_normalize(&mut self) {
         println!("reservation filter normalizing");
-        if self.status == ReservationStatus::Unknown as i32 {
+        if self.get_statuses().is_empty() {
             self.status = ReservationStatus::Pending as i32
         }
     }
 }
 
 impl ToSql for ReservationFilter {
-    fn to_sql(&self) -> String {
+    fn to_query(&self, tenant_id: &str) -> QueryBuilder<'static, Postgres> {
+        self.page_query(tenant_id, None)
+    }
+}
+
+impl ReservationFilter {
+    pub fn get_status(&self) -> ReservationStatus {
+        ReservationStatus::from_i32(self.status).unwrap()
+    }
+
+    pub fn get_sort_by(&self) -> ReservationSortBy {
+        ReservationSortBy::from_i32(self.sort_by).unwrap()
+    }
+
+    /// statuses to match, from status and statuses
+    pub fn get_statuses(&self) -> Vec<ReservationStatus> {
+        merge_statuses(self.status, &self.statuses)
+    }
+
+    /// query of the page of the tenant starting from the cursor. Pages are sorted by
+    /// `(sort key, id)`, the sort key of the cursor is `cursor_key` if given, otherwise it's
+    /// looked up by the id
+    pub fn page_query(
+        &self,
+        tenant_id: &str,
+        cursor_key: Option<&str>,
+    ) -> QueryBuilder<'static, Postgres> {
         let middle_plus = i64::from(self.cursor.is_some());
         let limit = self.page_size + 1 + middle_plus;
 
-        let status = self.get_status();
+        let sort_by = self.get_sort_by();
 
-        let cursor_cond = if self.desc {
-            format!("id <= {}", self.get_cursor())
-        } else {
-            format!("id >= {}", self.get_cursor())
+        let op = if self.desc { "<=" } else { ">=" };
+        let direction = if self.desc { "DESC" } else { "ASC" };
+        let order = match sort_by {
+            ReservationSortBy::Id => format!("id {direction}"),
+            _ => format!("{} {direction}, id {direction}", sort_by.column()),
         };
 
-        let user_resource_cond = get_user_resource_cond(&self.user_id, &self.resource_id);
+        let mut builder = QueryBuilder::new("SELECT * FROM rsvp.reservations WHERE ");
+        self.push_conds(&mut builder, tenant_id);
+        match (sort_by, self.cursor) {
+            (ReservationSortBy::Id, _) => {
+                builder.push(format_args!(" AND id {op} {}", self.get_cursor()));
+            }
+            (_, None) => {
+                builder.push(" AND TRUE");
+            }
+            (_, Some(cursor)) => {
+                let column = sort_by.column();
+                builder.push(format_args!(" AND ({column}, id) {op} ("));
+                match cursor_key {
+                    Some(key) => {
+                        builder
+                            .push_bind(key.to_string())
+                            .push(format_args!("::{}", sort_by.sql_type()));
+                    }
+                    None => {
+                        builder.push(format_args!(
+                            "(SELECT {column} FROM rsvp.reservations WHERE id = {cursor})"
+                        ));
+                    }
+                }
+                builder.push(format_args!(", {cursor})"));
+            }
+        }
+        builder.push(format_args!(" ORDER BY {order} LIMIT {limit}"));
 
-        let direction = if self.desc { "DESC" } else { "ASC" };
+        builder
+    }
+
+    /// query of the reservations of the tenant matching the filter on all pages, after
+    /// `prefix`, used to count the total
+    pub fn total_query(&self, tenant_id: &str, prefix: &str) -> QueryBuilder<'static, Postgres> {
+        let mut builder = QueryBuilder::new(prefix);
+        builder.push("SELECT * FROM rsvp.reservations WHERE ");
+        self.push_conds(&mut builder, tenant_id);
 
-        format!("SELECT * FROM rsvp.reservations WHERE status = '{status}'::rsvp.reservation_status AND {cursor_cond} AND {user_resource_cond} ORDER BY id {direction} LIMIT {limit}")
+        builder
     }
-}
 
-impl ReservationFilter {
-    pub fn get_status(&self) -> ReservationStatus {
-        ReservationStatus::from_i32(self.status).unwrap()
+    /// conditions on the tenant, statuses, users and resources, the time range and the note.
+    /// The note search must match the expression of reservations_note_search_idx to use the
+    /// index
+    fn push_conds(&self, builder: &mut QueryBuilder<'static, Postgres>, tenant_id: &str) {
+        push_reservation_conds(
+            builder,
+            tenant_id,
+            &self.get_statuses(),
+            merge_values(&self.user_id, &self.user_ids),
+            merge_values(&self.resource_id, &self.resource_ids),
+        );
+
+        if self.start.is_some() || self.end.is_some() {
+            builder.push(format_args!(
+                " AND timespan && tstzrange('{}', '{}')",
+                get_time_string(self.start.as_ref(), true),
+                get_time_string(self.end.as_ref(), false)
+            ));
+        }
+
+        let note_query = self.note_query.trim();
+        if !note_query.is_empty() {
+            builder
+                .push(" AND to_tsvector('english', coalesce(note, '')) @@ websearch_to_tsquery('english', ")
+                .push_bind(note_query.to_string())
+                .push(")");
+        }
     }
 
     pub fn get_cursor(&self) -> i64 {
         self.cursor.unwrap_or(if self.desc { i64::MAX } else { 0 })
     }
 
-    pub fn get_pager<T: Id>(&self, data: &mut VecDeque<T>) -> FilterPager {
-        let page_info = self.page_info();
+    /// pager of the fetched page, the page tokens are signed with `secret`
+    pub fn get_pager<T: Id>(&self, data: &mut VecDeque<T>, secret: &[u8]) -> FilterPager {
+        let page_info = PageInfo {
+            signer: Some(PageSigner {
+                secret: secret.to_vec(),
+                sort_by: self.get_sort_by().to_string(),
+                filter: self.digest(),
+            }),
+            ..self.page_info()
+        };
         let pager = page_info.get_pager(data);
 
         pager.into()
     }
 
+    /// move the position in the page token to the cursor, and return the sort key of the
+    /// cursor. The token should be signed with `secret` and issued for the same filter
+    /// conditions and sort order
+    pub fn apply_page_token(&mut self, secret: &[u8]) -> Result<Option<String>, Error> {
+        if self.page_token.is_empty() {
+            return Ok(None);
+        }
+
+        let token = PageToken::decode(&self.page_token, secret)?;
+        if token.sort_by != self.get_sort_by().to_string()
+            || token.desc != self.desc
+            || token.filter != self.digest()
+        {
+            return Err(Error::InvalidCursor(
+                "page token was issued for another filter".into(),
+            ));
+        }
+
+        self.cursor = Some(token.cursor);
+        self.page_token.clear();
+
+        Ok(Some(token.key))
+    }
+
+    /// digest of the conditions which select the reservations, regardless of the page
+    pub fn digest(&self) -> String {
+        let mut statuses: Vec<_> = self.get_statuses().iter().map(|s| s.to_string()).collect();
+        let mut user_ids = merge_values(&self.user_id, &self.user_ids);
+        let mut resource_ids = merge_values(&self.resource_id, &self.resource_ids);
+        for values in [&mut statuses, &mut user_ids, &mut resource_ids] {
+            values.sort();
+        }
+
+        let mut hasher = Sha256::new();
+        for values in [
+            statuses,
+            user_ids,
+            resource_ids,
+            vec![get_time_string(self.start.as_ref(), true)],
+            vec![get_time_string(self.end.as_ref(), false)],
+            vec![self.note_query.trim().to_string()],
+        ] {
+            for value in values {
+                hasher.update(value.as_bytes());
+                hasher.update([0]);
+            }
+            hasher.update([1]);
+        }
+
+        hasher.finalize()[..8]
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+
     pub fn next_page(&self, pager: &FilterPager) -> Option<Self> {
         let page_info = self.page_info();
         let pager = pager.into();
@@ -94,6 +254,16 @@ impl ReservationFilter {
             cursor: pi.cursor,
             page_size: pi.page_size,
             desc: pi.desc,
+            include_total: self.include_total,
+            approximate_total: self.approximate_total,
+            page_token: String::new(),
+            sort_by: self.sort_by,
+            start: self.start.clone(),
+            end: self.end.clone(),
+            note_query: self.note_query.clone(),
+            resource_ids: self.resource_ids.clone(),
+            user_ids: self.user_ids.clone(),
+            statuses: self.statuses.clone(),
         })
     }
 
@@ -102,6 +272,7 @@ impl ReservationFilter {
             cursor: self.cursor,
             page_size: self.page_size,
             desc: self.desc,
+            signer: None,
         }
     }
 }
@@ -121,7 +292,7 @@ mod tests {
 
         assert_eq!(
             sql,
-            "SELECT * FROM rsvp.reservations WHERE status = 'pending'::rsvp.reservation_status AND id >= 0 AND user_id = 'james id' ORDER BY id ASC LIMIT 11"
+            "SELECT * FROM rsvp.reservations WHERE tenant_id = $1 AND status = ANY($2::rsvp.reservation_status[]) AND user_id = ANY($3) AND id >= 0 ORDER BY id ASC LIMIT 11"
         );
 
         let filter = ReservationFilterBuilder::default()
@@ -132,7 +303,7 @@ mod tests {
         let sql = filter.to_sql();
         assert_eq!(
             sql,
-            "SELECT * FROM rsvp.reservations WHERE status = 'pending'::rsvp.reservation_status AND id >= 0 AND user_id = 'james id' AND resource_id = 'test' ORDER BY id ASC LIMIT 11"
+            "SELECT * FROM rsvp.reservations WHERE tenant_id = $1 AND status = ANY($2::rsvp.reservation_status[]) AND user_id = ANY($3) AND resource_id = ANY($4) AND id >= 0 ORDER BY id ASC LIMIT 11"
         );
 
         let filter = ReservationFilterBuilder::default()
@@ -143,7 +314,7 @@ mod tests {
         let sql = filter.to_sql();
         assert_eq!(
             sql,
-            "SELECT * FROM rsvp.reservations WHERE status = 'pending'::rsvp.reservation_status AND id <= 9223372036854775807 AND TRUE ORDER BY id DESC LIMIT 11"
+            "SELECT * FROM rsvp.reservations WHERE tenant_id = $1 AND status = ANY($2::rsvp.reservation_status[]) AND id <= 9223372036854775807 ORDER BY id DESC LIMIT 11"
         );
 
         let filter = ReservationFilterBuilder::default()
@@ -155,7 +326,7 @@ mod tests {
         let sql = filter.to_sql();
         assert_eq!(
             sql,
-            "SELECT * FROM rsvp.reservations WHERE status = 'pending'::rsvp.reservation_status AND id >= 100 AND user_id = 'james id' ORDER BY id ASC LIMIT 12"
+            "SELECT * FROM rsvp.reservations WHERE tenant_id = $1 AND status = ANY($2::rsvp.reservation_status[]) AND user_id = ANY($3) AND id >= 100 ORDER BY id ASC LIMIT 12"
         );
 
         let filter = ReservationFilterBuilder::default()
@@ -168,7 +339,22 @@ mod tests {
         let sql = filter.to_sql();
         assert_eq!(
             sql,
-            "SELECT * FROM rsvp.reservations WHERE status = 'pending'::rsvp.reservation_status AND id <= 10 AND user_id = 'james id' ORDER BY id DESC LIMIT 12"
+            "SELECT * FROM rsvp.reservations WHERE tenant_id = $1 AND status = ANY($2::rsvp.reservation_status[]) AND user_id = ANY($3) AND id <= 10 ORDER BY id DESC LIMIT 12"
+        );
+    }
+
+    #[test]
+    fn filter_total_sql_should_ignore_cursor() {
+        let filter = ReservationFilterBuilder::default()
+            .user_id("james id")
+            .cursor(100)
+            .include_total(true)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            filter.total_query("", "").into_sql(),
+            "SELECT * FROM rsvp.reservations WHERE tenant_id = $1 AND status = ANY($2::rsvp.reservation_status[]) AND user_id = ANY($3)"
         );
     }
 
@@ -179,7 +365,7 @@ mod tests {
             .build()
             .unwrap();
         let mut items = generate_test_ids(1, 11);
-        let pager = filter.get_pager(&mut items);
+        let pager = filter.get_pager(&mut items, b"secret");
         assert_eq!(pager.prev, None);
         assert_eq!(pager.next, Some(10));
 
@@ -187,11 +373,162 @@ mod tests {
         let sql = filter.to_sql();
         assert_eq!(
             sql,
-            "SELECT * FROM rsvp.reservations WHERE status = 'pending'::rsvp.reservation_status AND id >= 10 AND resource_id = 'test' ORDER BY id ASC LIMIT 12"
+            "SELECT * FROM rsvp.reservations WHERE tenant_id = $1 AND status = ANY($2::rsvp.reservation_status[]) AND resource_id = ANY($3) AND id >= 10 ORDER BY id ASC LIMIT 12"
         );
         let mut items = generate_test_ids(10, 20);
-        let pager = filter.get_pager(&mut items);
+        let pager = filter.get_pager(&mut items, b"secret");
         assert_eq!(pager.prev, Some(11));
         assert_eq!(pager.next, None);
     }
+
+    #[test]
+    fn filter_should_apply_page_token_of_same_filter() {
+        let filter = ReservationFilterBuilder::default()
+            .resource_id("test")
+            .build()
+            .unwrap();
+        let mut items = generate_test_ids(1, 11);
+        let pager = filter.get_pager(&mut items, b"secret");
+        assert!(pager.prev_token.is_empty());
+
+        let mut next = ReservationFilter {
+            page_token: pager.next_token.clone(),
+            ..filter.clone()
+        };
+        assert_eq!(next.apply_page_token(b"secret").unwrap(), Some("10".into()));
+        assert_eq!(next.cursor, Some(10));
+        assert!(next.page_token.is_empty());
+
+        let mut other = ReservationFilterBuilder::default()
+            .resource_id("another")
+            .build()
+            .unwrap();
+        other.page_token = pager.next_token.clone();
+        assert!(matches!(
+            other.apply_page_token(b"secret"),
+            Err(Error::InvalidCursor(_))
+        ));
+
+        let mut reversed = ReservationFilter {
+            desc: true,
+            page_token: pager.next_token,
+            ..filter
+        };
+        assert!(matches!(
+            reversed.apply_page_token(b"secret"),
+            Err(Error::InvalidCursor(_))
+        ));
+    }
+
+    #[test]
+    fn filter_sorted_by_start_should_use_keyset() {
+        let filter = ReservationFilterBuilder::default()
+            .user_id("james id")
+            .sort_by(ReservationSortBy::Start)
+            .build()
+            .unwrap();
+        assert_eq!(
+            filter.to_sql(),
+            "SELECT * FROM rsvp.reservations WHERE tenant_id = $1 AND status = ANY($2::rsvp.reservation_status[]) AND user_id = ANY($3) AND TRUE ORDER BY lower(timespan) ASC, id ASC LIMIT 11"
+        );
+
+        let filter = ReservationFilter {
+            cursor: Some(10),
+            desc: true,
+            ..filter
+        };
+        assert_eq!(
+            filter
+                .page_query("", Some("2023-01-02T09:00:00+00:00"))
+                .into_sql(),
+            "SELECT * FROM rsvp.reservations WHERE tenant_id = $1 AND status = ANY($2::rsvp.reservation_status[]) AND user_id = ANY($3) AND (lower(timespan), id) <= ($4::timestamptz, 10) ORDER BY lower(timespan) DESC, id DESC LIMIT 12"
+        );
+
+        // raw cursor looks up the sort key
+        assert_eq!(
+            filter.to_sql(),
+            "SELECT * FROM rsvp.reservations WHERE tenant_id = $1 AND status = ANY($2::rsvp.reservation_status[]) AND user_id = ANY($3) AND (lower(timespan), id) <= ((SELECT lower(timespan) FROM rsvp.reservations WHERE id = 10), 10) ORDER BY lower(timespan) DESC, id DESC LIMIT 12"
+        );
+
+        // the key of the token is bound, whatever it contains
+        let filter = ReservationFilter {
+            sort_by: ReservationSortBy::UserId as i32,
+            ..filter
+        };
+        assert_eq!(
+            filter.page_query("", Some("o'brien id")).into_sql(),
+            "SELECT * FROM rsvp.reservations WHERE tenant_id = $1 AND status = ANY($2::rsvp.reservation_status[]) AND user_id = ANY($3) AND (user_id, id) <= ($4::text, 10) ORDER BY user_id DESC, id DESC LIMIT 12"
+        );
+    }
+
+    #[test]
+    fn filter_with_time_range_and_note_should_generate_correct_sql() {
+        let filter = ReservationFilterBuilder::default()
+            .start(
+                "2023-02-01T00:00:00Z"
+                    .parse::<prost_types::Timestamp>()
+                    .unwrap(),
+            )
+            .note_query("wheelchair")
+            .build()
+            .unwrap();
+        assert_eq!(
+            filter.to_sql(),
+            "SELECT * FROM rsvp.reservations WHERE tenant_id = $1 AND status = ANY($2::rsvp.reservation_status[]) AND timespan && tstzrange('2023-02-01T00:00:00+00:00', 'infinity') AND to_tsvector('english', coalesce(note, '')) @@ websearch_to_tsquery('english', $3) AND id >= 0 ORDER BY id ASC LIMIT 11"
+        );
+
+        let other = ReservationFilter {
+            note_query: "wheelchairs".into(),
+            ..filter.clone()
+        };
+        assert_ne!(filter.digest(), other.digest());
+
+        let invalid = ReservationFilter {
+            end: filter.start.clone(),
+            ..filter
+        };
+        assert!(matches!(invalid.validate(), Err(Error::InvalidTime)));
+    }
+
+    #[test]
+    fn filter_page_token_should_follow_repeated_values() {
+        let filter = ReservationFilterBuilder::default()
+            .resource_ids(vec!["room 1".to_string(), "room 2".to_string()])
+            .statuses(vec![
+                ReservationStatus::Pending as i32,
+                ReservationStatus::Confirmed as i32,
+            ])
+            .build()
+            .unwrap();
+        assert_eq!(
+            filter.to_sql(),
+            "SELECT * FROM rsvp.reservations WHERE tenant_id = $1 AND status = ANY($2::rsvp.reservation_status[]) AND resource_id = ANY($3) AND id >= 0 ORDER BY id ASC LIMIT 11"
+        );
+
+        let mut items = generate_test_ids(1, 11);
+        let pager = filter.get_pager(&mut items, b"secret");
+
+        // the token is accepted with the same conditions in another order
+        let mut same = ReservationFilterBuilder::default()
+            .resource_id("room 2")
+            .resource_ids(vec!["room 1".to_string()])
+            .status(ReservationStatus::Confirmed)
+            .statuses(vec![ReservationStatus::Pending as i32])
+            .page_token(pager.next_token.clone())
+            .build()
+            .unwrap();
+        assert_eq!(same.apply_page_token(b"secret").unwrap(), Some("10".into()));
+        assert_eq!(same.cursor, Some(10));
+
+        // but not with fewer statuses
+        let mut other = ReservationFilter {
+            statuses: vec![ReservationStatus::Pending as i32],
+            page_token: pager.next_token,
+            ..filter
+        };
+        assert!(matches!(
+            other.apply_page_token(b"secret"),
+            Err(Error::InvalidCursor(_))
+        ));
+    }
 }
@@ -0,0 +1,13 @@
+use crate::ReservationMatchMode;
+
+impl ReservationMatchMode {
+    /// sql condition on the timespan of the reservations for the range expression
+    pub fn cond(&self, range: &str) -> String {
+        match self {
+            ReservationMatchMode::ContainedIn => format!("{range} @> timespan"),
+            ReservationMatchMode::Overlaps => format!("{range} && timespan"),
+            ReservationMatchMode::Contains => format!("timespan @> {range}"),
+            ReservationMatchMode::StartsWithin => format!("{range} @> lower(timespan)"),
+        }
+    }
+}
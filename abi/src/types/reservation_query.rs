@@ -1,10 +1,16 @@
-use prost_types::Timestamp;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use sqlx::{Postgres, QueryBuilder};
 
 use crate::{
-    convert_to_utc_time, get_user_resource_cond, Error, Normalize, ReservationQuery,
-    ReservationQueryBuilder, ReservationStatus, ToSql, Validator,
+    get_time_string, merge_statuses, merge_values, pager, push_reservation_conds, Error, Normalize,
+    ReservationMatchMode, ReservationQuery, ReservationQueryBuilder, ReservationStatus, ToSql,
+    Validator,
 };
 
+/// start and id of the reservation a query resumes after
+pub type ResumeKey = (DateTime<Utc>, i64);
+
 impl ReservationQueryBuilder {
     pub fn build(&self) -> Result<ReservationQuery, Error> {
         let mut query = self
@@ -21,11 +27,91 @@ impl ReservationQuery {
     pub fn get_status(&self) -> ReservationStatus {
         ReservationStatus::from_i32(self.status).unwrap()
     }
+
+    /// statuses to match, from status and statuses
+    pub fn get_statuses(&self) -> Vec<ReservationStatus> {
+        merge_statuses(self.status, &self.statuses)
+    }
+
+    pub fn get_match_mode(&self) -> ReservationMatchMode {
+        ReservationMatchMode::from_i32(self.match_mode).unwrap()
+    }
+
+    /// take the resume token, and return the start and id of the reservation to resume
+    /// after. The token should be signed with `secret`
+    pub fn apply_resume_token(&mut self, secret: &[u8]) -> Result<Option<ResumeKey>, Error> {
+        if self.resume_token.is_empty() {
+            return Ok(None);
+        }
+
+        let token = std::mem::take(&mut self.resume_token);
+        let invalid = || Error::InvalidCursor(format!("malformed resume token {token}"));
+        let payload = pager::verify(&token, secret, "resume token")?;
+        let payload = URL_SAFE_NO_PAD.decode(payload).map_err(|_| invalid())?;
+        let payload = String::from_utf8(payload).map_err(|_| invalid())?;
+        let (start, id) = payload.split_once(' ').ok_or_else(invalid)?;
+        let start = DateTime::parse_from_rfc3339(start).map_err(|_| invalid())?;
+        let id = id.parse().map_err(|_| invalid())?;
+
+        Ok(Some((start.with_timezone(&Utc), id)))
+    }
+
+    /// query of the reservations of the tenant, after the reservation of the resume key if
+    /// given
+    pub fn resume_query(
+        &self,
+        tenant_id: &str,
+        resume_key: Option<ResumeKey>,
+    ) -> QueryBuilder<'static, Postgres> {
+        let direction = if self.desc { "DESC" } else { "ASC" };
+        let timespan = format!(
+            "tstzrange('{}', '{}')",
+            get_time_string(self.start.as_ref(), true),
+            get_time_string(self.end.as_ref(), false)
+        );
+
+        let time_cond = self.get_match_mode().cond(&timespan);
+
+        let mut builder = QueryBuilder::new(format!(
+            "SELECT * FROM rsvp.reservations WHERE {time_cond} AND "
+        ));
+        push_reservation_conds(
+            &mut builder,
+            tenant_id,
+            &self.get_statuses(),
+            merge_values(&self.user_id, &self.user_ids),
+            merge_values(&self.resource_id, &self.resource_ids),
+        );
+
+        if let Some((start, id)) = resume_key {
+            let op = if self.desc { "<" } else { ">" };
+            builder
+                .push(format_args!(" AND (lower(timespan), id) {op} ("))
+                .push_bind(start)
+                .push(", ")
+                .push_bind(id)
+                .push(")");
+        }
+
+        builder.push(format_args!(
+            " ORDER BY lower(timespan) {direction}, id {direction}"
+        ));
+        if self.limit > 0 {
+            builder.push(format_args!(" LIMIT {}", self.limit));
+        }
+
+        builder
+    }
 }
 
 impl Validator for ReservationQuery {
     fn validate(&self) -> Result<(), Error> {
-        ReservationStatus::from_i32(self.status).ok_or(Error::InvalidStatus(self.status))?;
+        for status in self.statuses.iter().chain([&self.status]) {
+            ReservationStatus::from_i32(*status).ok_or(Error::InvalidStatus(*status))?;
+        }
+
+        ReservationMatchMode::from_i32(self.match_mode)
+            .ok_or(Error::InvalidMatchMode(self.match_mode))?;
 
         if let (Some(start), Some(end)) = (self.start.as_ref(), self.end.as_ref()) {
             if start.seconds >= end.seconds {
@@ -33,46 +119,32 @@ impl Validator for ReservationQuery {
             }
         }
 
+        if self.limit < 0 {
+            return Err(Error::InvalidLimit(self.limit));
+        }
+
         Ok(())
     }
 }
 
 impl Normalize for ReservationQuery {
     fn do_normalize(&mut self) {
-        if self.status == ReservationStatus::Unknown as i32 {
+        if self.get_statuses().is_empty() {
             self.status = ReservationStatus::Pending as i32
         }
     }
 }
 
 impl ToSql for ReservationQuery {
-    fn to_sql(&self) -> String {
-        let status = self.get_status();
-        let direction = if self.desc { "DESC" } else { "ASC" };
-        let timespan = format!(
-            "tstzrange('{}', '{}')",
-            get_time_string(self.start.as_ref(), true),
-            get_time_string(self.end.as_ref(), false)
-        );
-
-        let user_resource_cond = get_user_resource_cond(&self.user_id, &self.resource_id);
-
-        format!(
-            "SELECT * FROM rsvp.reservations WHERE {timespan} @> timespan AND status = '{status}'::rsvp.reservation_status AND {user_resource_cond} ORDER BY lower(timespan) {direction}"
-        )
-    }
-}
-
-fn get_time_string(ts: Option<&Timestamp>, start: bool) -> String {
-    match ts {
-        Some(ts) => convert_to_utc_time(ts).to_rfc3339(),
-        None => (if start { "-infinity" } else { "infinity" }).into(),
+    fn to_query(&self, tenant_id: &str) -> QueryBuilder<'static, Postgres> {
+        self.resume_query(tenant_id, None)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use prost_types::Timestamp;
 
     #[test]
     fn query_should_generate_valid_sql() {
@@ -83,7 +155,7 @@ mod tests {
 
         let sql = query.to_sql();
 
-        assert_eq!(sql, "SELECT * FROM rsvp.reservations WHERE tstzrange('-infinity', 'infinity') @> timespan AND status = 'pending'::rsvp.reservation_status AND user_id = 'james id' ORDER BY lower(timespan) ASC");
+        assert_eq!(sql, "SELECT * FROM rsvp.reservations WHERE tstzrange('-infinity', 'infinity') @> timespan AND tenant_id = $1 AND status = ANY($2::rsvp.reservation_status[]) AND user_id = ANY($3) ORDER BY lower(timespan) ASC, id ASC");
 
         let query = ReservationQueryBuilder::default()
             .resource_id("test")
@@ -92,7 +164,7 @@ mod tests {
             .unwrap();
 
         let sql = query.to_sql();
-        assert_eq!(sql, "SELECT * FROM rsvp.reservations WHERE tstzrange('2021-11-01T22:00:00+00:00', 'infinity') @> timespan AND status = 'pending'::rsvp.reservation_status AND resource_id = 'test' ORDER BY lower(timespan) ASC");
+        assert_eq!(sql, "SELECT * FROM rsvp.reservations WHERE tstzrange('2021-11-01T22:00:00+00:00', 'infinity') @> timespan AND tenant_id = $1 AND status = ANY($2::rsvp.reservation_status[]) AND resource_id = ANY($3) ORDER BY lower(timespan) ASC, id ASC");
 
         let query = ReservationQueryBuilder::default()
             .end("2021-11-01T16:00:00-0700".parse::<Timestamp>().unwrap())
@@ -100,6 +172,83 @@ mod tests {
             .unwrap();
 
         let sql = query.to_sql();
-        assert_eq!(sql, "SELECT * FROM rsvp.reservations WHERE tstzrange('-infinity', '2021-11-01T23:00:00+00:00') @> timespan AND status = 'pending'::rsvp.reservation_status AND TRUE ORDER BY lower(timespan) ASC");
+        assert_eq!(sql, "SELECT * FROM rsvp.reservations WHERE tstzrange('-infinity', '2021-11-01T23:00:00+00:00') @> timespan AND tenant_id = $1 AND status = ANY($2::rsvp.reservation_status[]) ORDER BY lower(timespan) ASC, id ASC");
+    }
+
+    #[test]
+    fn query_should_merge_single_and_repeated_values() {
+        let query = ReservationQueryBuilder::default()
+            .resource_id("room 1")
+            .resource_ids(vec!["room 2".to_string(), "room 1".to_string()])
+            .statuses(vec![
+                ReservationStatus::Confirmed as i32,
+                ReservationStatus::Pending as i32,
+            ])
+            .build()
+            .unwrap();
+
+        assert_eq!(query.status, ReservationStatus::Unknown as i32);
+        assert_eq!(
+            query.get_statuses(),
+            vec![ReservationStatus::Confirmed, ReservationStatus::Pending]
+        );
+        assert_eq!(
+            merge_values(&query.resource_id, &query.resource_ids),
+            vec!["room 2", "room 1"]
+        );
+        assert_eq!(query.to_sql(), "SELECT * FROM rsvp.reservations WHERE tstzrange('-infinity', 'infinity') @> timespan AND tenant_id = $1 AND status = ANY($2::rsvp.reservation_status[]) AND resource_id = ANY($3) ORDER BY lower(timespan) ASC, id ASC");
+
+        let query = ReservationQuery {
+            statuses: vec![42],
+            ..query
+        };
+        assert!(matches!(query.validate(), Err(Error::InvalidStatus(42))));
+    }
+
+    #[test]
+    fn query_should_match_mode_and_resume() {
+        let query = ReservationQueryBuilder::default()
+            .resource_id("test")
+            .match_mode(ReservationMatchMode::Overlaps)
+            .limit(100)
+            .build()
+            .unwrap();
+        assert_eq!(query.to_sql(), "SELECT * FROM rsvp.reservations WHERE tstzrange('-infinity', 'infinity') && timespan AND tenant_id = $1 AND status = ANY($2::rsvp.reservation_status[]) AND resource_id = ANY($3) ORDER BY lower(timespan) ASC, id ASC LIMIT 100");
+
+        let rsvp = crate::Reservation {
+            id: 42,
+            start: Some("2021-11-01T15:00:00-0700".parse::<Timestamp>().unwrap()),
+            ..Default::default()
+        };
+        let mut query = ReservationQuery {
+            match_mode: ReservationMatchMode::StartsWithin as i32,
+            resume_token: rsvp.sign_resume_token(b"secret"),
+            desc: true,
+            limit: 0,
+            ..query
+        };
+        let resume_key = query.apply_resume_token(b"secret").unwrap();
+        assert_eq!(
+            resume_key,
+            Some(("2021-11-01T22:00:00Z".parse().unwrap(), 42))
+        );
+        assert!(query.resume_token.is_empty());
+        assert_eq!(query.resume_query("", resume_key).into_sql(), "SELECT * FROM rsvp.reservations WHERE tstzrange('-infinity', 'infinity') @> lower(timespan) AND tenant_id = $1 AND status = ANY($2::rsvp.reservation_status[]) AND resource_id = ANY($3) AND (lower(timespan), id) < ($4, $5) ORDER BY lower(timespan) DESC, id DESC");
+
+        // tokens which are malformed or signed with another secret
+        for token in [
+            "not a token".to_string(),
+            URL_SAFE_NO_PAD.encode("2021-11-01T22:00:00Z 42"),
+            rsvp.sign_resume_token(b"another secret"),
+        ] {
+            let mut query = ReservationQuery {
+                resume_token: token,
+                ..query.clone()
+            };
+            assert!(matches!(
+                query.apply_resume_token(b"secret"),
+                Err(Error::InvalidCursor(_))
+            ));
+        }
     }
 }
@@ -0,0 +1,40 @@
+use crate::ReservationSortBy;
+use std::fmt;
+
+impl ReservationSortBy {
+    /// sql expression of the sort key
+    pub fn column(&self) -> &'static str {
+        match self {
+            ReservationSortBy::Id => "id",
+            ReservationSortBy::Start => "lower(timespan)",
+            ReservationSortBy::End => "upper(timespan)",
+            ReservationSortBy::UserId => "user_id",
+            ReservationSortBy::ResourceId => "resource_id",
+            ReservationSortBy::CreatedAt => "created_at",
+        }
+    }
+
+    /// sql type of the sort key, which the bound value of a page token is cast to
+    pub fn sql_type(&self) -> &'static str {
+        match self {
+            ReservationSortBy::Id => "int8",
+            ReservationSortBy::Start | ReservationSortBy::End | ReservationSortBy::CreatedAt => {
+                "timestamptz"
+            }
+            ReservationSortBy::UserId | ReservationSortBy::ResourceId => "text",
+        }
+    }
+}
+
+impl fmt::Display for ReservationSortBy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReservationSortBy::Id => write!(f, "id"),
+            ReservationSortBy::Start => write!(f, "start"),
+            ReservationSortBy::End => write!(f, "end"),
+            ReservationSortBy::UserId => write!(f, "user_id"),
+            ReservationSortBy::ResourceId => write!(f, "resource_id"),
+            ReservationSortBy::CreatedAt => write!(f, "created_at"),
+        }
+    }
+}
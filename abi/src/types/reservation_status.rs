@@ -1,5 +1,5 @@
-use crate::{ReservationStatus, RsvpStatus};
-use std::fmt;
+use crate::{Error, ReservationStatus, RsvpStatus};
+use std::{fmt, str::FromStr};
 
 impl fmt::Display for ReservationStatus {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -7,11 +7,28 @@ impl fmt::Display for ReservationStatus {
             ReservationStatus::Pending => write!(f, "pending"),
             ReservationStatus::Blocked => write!(f, "blocked"),
             ReservationStatus::Confirmed => write!(f, "confirmed"),
+            ReservationStatus::Expired => write!(f, "expired"),
             ReservationStatus::Unknown => write!(f, "unknown"),
         }
     }
 }
 
+impl FromStr for ReservationStatus {
+    type Err = Error;
+
+    /// the names of `Display`, e.g. `pending`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(ReservationStatus::Pending),
+            "blocked" => Ok(ReservationStatus::Blocked),
+            "confirmed" => Ok(ReservationStatus::Confirmed),
+            "expired" => Ok(ReservationStatus::Expired),
+            "unknown" => Ok(ReservationStatus::Unknown),
+            _ => Err(Error::InvalidStatusName(s.to_string())),
+        }
+    }
+}
+
 impl From<RsvpStatus> for ReservationStatus {
     fn from(rsvp: RsvpStatus) -> Self {
         match rsvp {
@@ -19,6 +36,7 @@ impl From<RsvpStatus> for ReservationStatus {
             RsvpStatus::Pending => Self::Pending,
             RsvpStatus::Confirmed => Self::Confirmed,
             RsvpStatus::Blocked => Self::Blocked,
+            RsvpStatus::Expired => Self::Expired,
         }
     }
 }
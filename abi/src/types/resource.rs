@@ -0,0 +1,211 @@
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+use sqlx::{
+    postgres::{types::PgRange, PgRow},
+    FromRow, Row,
+};
+use std::ops::Bound;
+
+use crate::{Error, Normalize, Resource, Validator};
+
+impl Resource {
+    pub fn new(
+        id: impl Into<String>,
+        resource_type: impl Into<String>,
+        display_name: impl Into<String>,
+        capacity: i32,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            resource_type: resource_type.into(),
+            display_name: display_name.into(),
+            capacity,
+            timezone: "UTC".into(),
+            attributes: "{}".into(),
+            active: true,
+            buffer_before_minutes: 0,
+            buffer_after_minutes: 0,
+            hold_ttl_seconds: 0,
+        }
+    }
+
+    pub fn with_buffers(mut self, before_minutes: i32, after_minutes: i32) -> Self {
+        self.buffer_before_minutes = before_minutes;
+        self.buffer_after_minutes = after_minutes;
+        self
+    }
+
+    /// extend the timespan of a reservation by the buffers of the resource
+    pub fn pad_timespan(&self, timespan: &PgRange<DateTime<Utc>>) -> PgRange<DateTime<Utc>> {
+        let pad = |bound: &Bound<DateTime<Utc>>, minutes: i32| match bound {
+            Bound::Included(t) => Bound::Included(*t + Duration::minutes(minutes as i64)),
+            Bound::Excluded(t) => Bound::Excluded(*t + Duration::minutes(minutes as i64)),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+
+        PgRange {
+            start: pad(&timespan.start, -self.buffer_before_minutes),
+            end: pad(&timespan.end, self.buffer_after_minutes),
+        }
+    }
+
+    pub fn has_buffers(&self) -> bool {
+        self.buffer_before_minutes > 0 || self.buffer_after_minutes > 0
+    }
+
+    pub fn get_timezone(&self) -> Result<Tz, Error> {
+        self.timezone
+            .parse()
+            .map_err(|_| Error::InvalidTimezone(self.timezone.clone()))
+    }
+}
+
+impl Validator for Resource {
+    fn validate(&self) -> Result<(), Error> {
+        if self.id.is_empty() || self.id.len() > 64 {
+            return Err(Error::InvalidResourceId(self.id.clone()));
+        }
+
+        if self.capacity < 0 {
+            return Err(Error::InvalidCapacity(self.capacity));
+        }
+
+        if self.buffer_before_minutes < 0 {
+            return Err(Error::InvalidBuffer(self.buffer_before_minutes));
+        }
+
+        if self.buffer_after_minutes < 0 {
+            return Err(Error::InvalidBuffer(self.buffer_after_minutes));
+        }
+
+        if self.hold_ttl_seconds < 0 {
+            return Err(Error::InvalidHoldTtl(self.hold_ttl_seconds));
+        }
+
+        if !self.timezone.is_empty() {
+            self.get_timezone()?;
+        }
+
+        if !self.attributes.is_empty()
+            && serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&self.attributes)
+                .is_err()
+        {
+            return Err(Error::InvalidAttributes(self.attributes.clone()));
+        }
+
+        Ok(())
+    }
+}
+
+impl Normalize for Resource {
+    fn do_normalize(&mut self) {
+        if self.capacity == 0 {
+            self.capacity = 1;
+        }
+
+        if self.timezone.is_empty() {
+            self.timezone = "UTC".into();
+        }
+
+        if self.attributes.is_empty() {
+            self.attributes = "{}".into();
+        }
+    }
+}
+
+impl FromRow<'_, PgRow> for Resource {
+    fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
+        let attributes: serde_json::Value = row.get("attributes");
+
+        Ok(Self {
+            id: row.get("id"),
+            resource_type: row.get("resource_type"),
+            display_name: row.get("display_name"),
+            capacity: row.get("capacity"),
+            timezone: row.get("timezone"),
+            attributes: attributes.to_string(),
+            active: row.get("active"),
+            buffer_before_minutes: row.get("buffer_before_minutes"),
+            buffer_after_minutes: row.get("buffer_after_minutes"),
+            hold_ttl_seconds: row.get("hold_ttl_seconds"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resource_normalize_should_fill_defaults() {
+        let mut resource = Resource {
+            id: "room 518".into(),
+            ..Default::default()
+        };
+
+        resource.normalize().unwrap();
+
+        assert_eq!(resource.capacity, 1);
+        assert_eq!(resource.timezone, "UTC");
+        assert_eq!(resource.attributes, "{}");
+    }
+
+    #[test]
+    fn resource_validate_should_reject_invalid_fields() {
+        let resource = Resource::new("", "room", "Room 518", 1);
+        assert_eq!(
+            resource.validate(),
+            Err(Error::InvalidResourceId("".into()))
+        );
+
+        let resource = Resource::new("room 518", "room", "Room 518", -1);
+        assert_eq!(resource.validate(), Err(Error::InvalidCapacity(-1)));
+
+        let mut resource = Resource::new("room 518", "room", "Room 518", 1);
+        resource.timezone = "Mars/Olympus".into();
+        assert_eq!(
+            resource.validate(),
+            Err(Error::InvalidTimezone("Mars/Olympus".into()))
+        );
+
+        let mut resource = Resource::new("room 518", "room", "Room 518", 1);
+        resource.attributes = "[1, 2]".into();
+        assert_eq!(
+            resource.validate(),
+            Err(Error::InvalidAttributes("[1, 2]".into()))
+        );
+
+        let resource = Resource::new("room 518", "room", "Room 518", 1).with_buffers(-5, 0);
+        assert_eq!(resource.validate(), Err(Error::InvalidBuffer(-5)));
+
+        let mut resource = Resource::new("room 518", "room", "Room 518", 1);
+        resource.hold_ttl_seconds = -1;
+        assert_eq!(resource.validate(), Err(Error::InvalidHoldTtl(-1)));
+
+        let mut resource = Resource::new("room 518", "room", "Room 518", 12);
+        resource.timezone = "America/Los_Angeles".into();
+        resource.attributes = r#"{"floor": 5}"#.into();
+        assert!(resource.validate().is_ok());
+    }
+
+    #[test]
+    fn pad_timespan_should_extend_both_ends() {
+        let resource = Resource::new("room 518", "room", "Room 518", 1).with_buffers(15, 30);
+        let start: DateTime<Utc> = "2023-01-03T10:00:00Z".parse().unwrap();
+        let end: DateTime<Utc> = "2023-01-03T11:00:00Z".parse().unwrap();
+
+        let padded = resource.pad_timespan(&PgRange {
+            start: Bound::Included(start),
+            end: Bound::Excluded(end),
+        });
+
+        assert_eq!(
+            padded.start,
+            Bound::Included("2023-01-03T09:45:00Z".parse().unwrap())
+        );
+        assert_eq!(
+            padded.end,
+            Bound::Excluded("2023-01-03T11:30:00Z".parse().unwrap())
+        );
+    }
+}
@@ -0,0 +1,211 @@
+use chrono::{DateTime, Utc};
+use sqlx::{postgres::PgRow, FromRow, Postgres, QueryBuilder, Row};
+
+use crate::{
+    convert_to_timestamp, convert_to_utc_time, merge_statuses, push_reservation_conds,
+    validate_range, Error, ReservationStatus, ResourceStats, StatsInterval, StatsQuery, ToSql,
+    Validator,
+};
+
+/// max buckets of a resource in one query
+const MAX_BUCKETS: i64 = 1000;
+
+impl StatsInterval {
+    /// unit of `date_trunc`
+    pub fn unit(&self) -> &'static str {
+        match self {
+            StatsInterval::Hour => "hour",
+            StatsInterval::Day => "day",
+            StatsInterval::Week => "week",
+        }
+    }
+
+    pub fn seconds(&self) -> i64 {
+        match self {
+            StatsInterval::Hour => 3600,
+            StatsInterval::Day => 24 * 3600,
+            StatsInterval::Week => 7 * 24 * 3600,
+        }
+    }
+}
+
+impl StatsQuery {
+    pub fn get_interval(&self) -> StatsInterval {
+        StatsInterval::from_i32(self.interval).unwrap()
+    }
+
+    /// statuses to aggregate, the bookings if none is given: expired holds and blocks
+    /// were never booked
+    pub fn get_statuses(&self) -> Vec<ReservationStatus> {
+        let statuses = merge_statuses(ReservationStatus::Unknown as i32, &self.statuses);
+        if !statuses.is_empty() {
+            return statuses;
+        }
+
+        vec![ReservationStatus::Pending, ReservationStatus::Confirmed]
+    }
+}
+
+impl Validator for StatsQuery {
+    fn validate(&self) -> Result<(), Error> {
+        validate_range(self.start.as_ref(), self.end.as_ref())?;
+
+        let interval = StatsInterval::from_i32(self.interval).ok_or_else(|| {
+            Error::InvalidStatsQuery(format!("unknown interval {}", self.interval))
+        })?;
+
+        for status in &self.statuses {
+            ReservationStatus::from_i32(*status).ok_or(Error::InvalidStatus(*status))?;
+        }
+
+        let seconds = self.end.as_ref().unwrap().seconds - self.start.as_ref().unwrap().seconds;
+        if seconds / interval.seconds() > MAX_BUCKETS {
+            return Err(Error::InvalidStatsQuery(format!(
+                "more than {} {} buckets in the time range",
+                MAX_BUCKETS,
+                interval.unit()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl ToSql for StatsQuery {
+    /// reservations are sliced by the buckets of their resource and the time range, the
+    /// peak of a bucket is at the start of one of its slices. No-shows are the started
+    /// bookings which are still pending, whatever statuses are aggregated
+    fn to_query(&self, tenant_id: &str) -> QueryBuilder<'static, Postgres> {
+        let start = convert_to_utc_time(self.start.as_ref().unwrap());
+        let end = convert_to_utc_time(self.end.as_ref().unwrap());
+        let unit = self.get_interval().unit();
+
+        let mut builder =
+            QueryBuilder::new("WITH rsvps AS (SELECT * FROM rsvp.reservations WHERE ");
+        push_reservation_conds(
+            &mut builder,
+            tenant_id,
+            &self.get_statuses(),
+            self.user_ids.clone(),
+            self.resource_ids.clone(),
+        );
+        builder
+            .push(" AND timespan && tstzrange(")
+            .push_bind(start)
+            .push(", ")
+            .push_bind(end)
+            .push(format_args!(
+                ")), buckets AS (SELECT r.id AS rid, r.capacity, b AT TIME ZONE r.timezone AS bucket_start, \
+                (b + '1 {unit}'::interval) AT TIME ZONE r.timezone AS bucket_end \
+                FROM rsvp.resources r, generate_series(date_trunc('{unit}', "
+            ))
+            .push_bind(start)
+            .push(" AT TIME ZONE r.timezone), ")
+            .push_bind(end)
+            .push(format_args!(
+                " AT TIME ZONE r.timezone, '1 {unit}'::interval) b \
+                WHERE (r.tenant_id, r.id) IN (SELECT tenant_id, resource_id FROM rsvps)), \
+                slices AS (SELECT * FROM (SELECT b.rid, b.capacity, b.bucket_start, rs.quantity, rs.status, rs.timespan, rs.created_at, \
+                rs.timespan * tstzrange(b.bucket_start, b.bucket_end) * tstzrange("
+            ))
+            .push_bind(start)
+            .push(", ")
+            .push_bind(end)
+            .push(
+                ") AS slice FROM buckets b JOIN rsvps rs ON rs.resource_id = b.rid) t WHERE NOT isempty(slice)), \
+                peaks AS (SELECT p.rid, p.bucket_start, MAX(o.quantity) AS peak_quantity FROM slices p, \
+                LATERAL (SELECT SUM(o.quantity) AS quantity FROM slices o \
+                WHERE o.rid = p.rid AND o.bucket_start = p.bucket_start AND o.slice @> lower(p.slice)) o \
+                GROUP BY p.rid, p.bucket_start) \
+                SELECT s.rid AS resource_id, s.bucket_start AS bucket, COUNT(*) AS reservations, \
+                SUM(s.quantity * EXTRACT(EPOCH FROM upper(s.slice) - lower(s.slice)))::float8 / 3600 AS booked_hours, \
+                p.peak_quantity::int4 AS peak_quantity, \
+                p.peak_quantity::float8 / GREATEST(s.capacity, 1) AS peak_occupancy, \
+                COALESCE(COUNT(*) FILTER (WHERE lower(s.timespan) <= now() AND s.status = 'pending')::float8 \
+                / NULLIF(COUNT(*) FILTER (WHERE lower(s.timespan) <= now() AND s.status IN ('pending', 'confirmed')), 0), 0) AS no_show_rate, \
+                COALESCE(AVG(EXTRACT(EPOCH FROM lower(s.timespan) - s.created_at))::float8 / 3600, 0) AS avg_lead_hours \
+                FROM slices s JOIN peaks p ON p.rid = s.rid AND p.bucket_start = s.bucket_start \
+                GROUP BY s.rid, s.bucket_start, s.capacity, p.peak_quantity \
+                ORDER BY s.rid, s.bucket_start",
+            );
+
+        builder
+    }
+}
+
+impl FromRow<'_, PgRow> for ResourceStats {
+    fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
+        let bucket: DateTime<Utc> = row.get("bucket");
+
+        Ok(Self {
+            resource_id: row.get("resource_id"),
+            bucket: Some(convert_to_timestamp(&bucket)),
+            reservations: row.get("reservations"),
+            booked_hours: row.get("booked_hours"),
+            peak_quantity: row.get("peak_quantity"),
+            peak_occupancy: row.get("peak_occupancy"),
+            no_show_rate: row.get("no_show_rate"),
+            avg_lead_hours: row.get("avg_lead_hours"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost_types::Timestamp;
+
+    fn stats_query(start: &str, end: &str, interval: StatsInterval) -> StatsQuery {
+        StatsQuery {
+            start: Some(start.parse::<Timestamp>().unwrap()),
+            end: Some(end.parse::<Timestamp>().unwrap()),
+            interval: interval as i32,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn stats_query_should_limit_buckets() {
+        let query = stats_query(
+            "2023-01-01T00:00:00Z",
+            "2023-02-01T00:00:00Z",
+            StatsInterval::Hour,
+        );
+        assert!(query.validate().is_ok());
+
+        let query = stats_query(
+            "2023-01-01T00:00:00Z",
+            "2024-01-01T00:00:00Z",
+            StatsInterval::Hour,
+        );
+        assert!(matches!(query.validate(), Err(Error::InvalidStatsQuery(_))));
+
+        let query = StatsQuery {
+            interval: StatsInterval::Day as i32,
+            ..query
+        };
+        assert!(query.validate().is_ok());
+
+        let query = StatsQuery { end: None, ..query };
+        assert_eq!(query.validate(), Err(Error::InvalidTime));
+    }
+
+    #[test]
+    fn stats_query_should_default_to_bookings() {
+        let query = stats_query(
+            "2023-01-01T00:00:00Z",
+            "2023-02-01T00:00:00Z",
+            StatsInterval::Day,
+        );
+        assert_eq!(
+            query.get_statuses(),
+            vec![ReservationStatus::Pending, ReservationStatus::Confirmed]
+        );
+
+        let query = StatsQuery {
+            statuses: vec![ReservationStatus::Confirmed as i32],
+            ..query
+        };
+        assert_eq!(query.get_statuses(), vec![ReservationStatus::Confirmed]);
+    }
+}
@@ -0,0 +1,117 @@
+use chrono::{DateTime, FixedOffset, Utc};
+use sqlx::{
+    postgres::{types::PgRange, PgRow},
+    FromRow, Row,
+};
+use std::ops::Bound;
+
+use crate::{
+    convert_to_timestamp, Error, Reservation, ReservationStatus, Validator, WaitlistEntry,
+};
+
+use super::{get_timespan, validate_range};
+
+impl WaitlistEntry {
+    pub fn new(
+        uid: impl Into<String>,
+        rid: impl Into<String>,
+        start: DateTime<FixedOffset>,
+        end: DateTime<FixedOffset>,
+        note: impl Into<String>,
+    ) -> Self {
+        Self {
+            user_id: uid.into(),
+            resource_id: rid.into(),
+            start: Some(convert_to_timestamp(&start.with_timezone(&Utc))),
+            end: Some(convert_to_timestamp(&end.with_timezone(&Utc))),
+            note: note.into(),
+            quantity: 1,
+            ..Default::default()
+        }
+    }
+
+    pub fn get_timespan(&self) -> PgRange<DateTime<Utc>> {
+        get_timespan(self.start.as_ref(), self.end.as_ref())
+    }
+
+    /// the pending reservation the entry is promoted to
+    pub fn to_reservation(&self) -> Reservation {
+        Reservation {
+            user_id: self.user_id.clone(),
+            resource_id: self.resource_id.clone(),
+            start: self.start.clone(),
+            end: self.end.clone(),
+            note: self.note.clone(),
+            status: ReservationStatus::Pending as _,
+            quantity: self.quantity,
+            ..Default::default()
+        }
+    }
+}
+
+impl Validator for WaitlistEntry {
+    fn validate(&self) -> Result<(), Error> {
+        if self.user_id.is_empty() {
+            return Err(Error::InvalidUserId(self.user_id.clone()));
+        }
+
+        if self.resource_id.is_empty() {
+            return Err(Error::InvalidResourceId(self.resource_id.clone()));
+        }
+
+        if self.quantity < 0 {
+            return Err(Error::InvalidQuantity(self.quantity));
+        }
+
+        validate_range(self.start.as_ref(), self.end.as_ref())
+    }
+}
+
+impl FromRow<'_, PgRow> for WaitlistEntry {
+    fn from_row(row: &PgRow) -> Result<Self, sqlx::Error> {
+        let range: PgRange<DateTime<Utc>> = row.get("timespan");
+        let f = |b: Bound<DateTime<Utc>>| match b {
+            Bound::Included(v) | Bound::Excluded(v) => Some(convert_to_timestamp(&v)),
+            Bound::Unbounded => None,
+        };
+        let reservation_id: Option<i64> = row.get("reservation_id");
+        let note: Option<String> = row.get("note");
+
+        Ok(Self {
+            id: row.get("id"),
+            user_id: row.get("user_id"),
+            resource_id: row.get("resource_id"),
+            start: f(range.start),
+            end: f(range.end),
+            quantity: row.get("quantity"),
+            note: note.unwrap_or_default(),
+            reservation_id: reservation_id.unwrap_or_default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waitlist_entry_should_convert_to_pending_reservation() {
+        let entry = WaitlistEntry {
+            quantity: 2,
+            ..WaitlistEntry::new(
+                "alice id",
+                "room 518",
+                "2023-01-02T09:00:00-0700".parse().unwrap(),
+                "2023-01-02T10:00:00-0700".parse().unwrap(),
+                "waiting",
+            )
+        };
+        assert!(entry.validate().is_ok());
+
+        let rsvp = entry.to_reservation();
+        assert_eq!(rsvp.status, ReservationStatus::Pending as i32);
+        assert_eq!(rsvp.quantity, 2);
+        assert_eq!(rsvp.start, entry.start);
+        assert_eq!(rsvp.get_timespan(), entry.get_timespan());
+    }
+}
@@ -14,3 +14,50 @@ pub fn convert_to_timestamp(dt: &DateTime<Utc>) -> Timestamp {
         nanos: dt.timestamp_subsec_nanos() as _,
     }
 }
+
+/// parse an RFC 3339 time, e.g. `2022-12-25T15:00:00-07:00`
+pub fn parse_timestamp(s: &str) -> Result<Timestamp, crate::Error> {
+    let dt = DateTime::parse_from_rfc3339(s).map_err(|_| crate::Error::InvalidTime)?;
+    Ok(convert_to_timestamp(&dt.with_timezone(&Utc)))
+}
+
+/// serde of optional timestamps as RFC 3339 strings, e.g. `2022-12-25T22:00:00Z`
+pub(crate) mod rfc3339 {
+    use super::*;
+    use chrono::SecondsFormat;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(ts: &Option<Timestamp>, s: S) -> Result<S::Ok, S::Error> {
+        match ts {
+            Some(ts) => s.serialize_str(
+                &convert_to_utc_time(ts).to_rfc3339_opts(SecondsFormat::AutoSi, true),
+            ),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Timestamp>, D::Error> {
+        match Option::<String>::deserialize(d)? {
+            Some(s) => parse_timestamp(&s).map(Some).map_err(D::Error::custom),
+            None => Ok(None),
+        }
+    }
+}
+
+/// serde of reservation statuses by their names, e.g. `pending`
+pub(crate) mod status_name {
+    use crate::ReservationStatus;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(status: &i32, s: S) -> Result<S::Ok, S::Error> {
+        let status = ReservationStatus::from_i32(*status).unwrap_or(ReservationStatus::Unknown);
+        s.serialize_str(&status.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<i32, D::Error> {
+        let name = String::deserialize(d)?;
+        name.parse::<ReservationStatus>()
+            .map(|status| status as i32)
+            .map_err(D::Error::custom)
+    }
+}
@@ -0,0 +1,3 @@
+-- Add down migration script here
+ALTER TABLE rsvp.reservations DROP CONSTRAINT reservations_resource_id_fkey;
+DROP TABLE rsvp.resources CASCADE;
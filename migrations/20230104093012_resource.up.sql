@@ -0,0 +1,21 @@
+-- Add up migration script here
+CREATE TABLE rsvp.resources (
+    id VARCHAR(64) NOT NULL,
+    resource_type VARCHAR(64) NOT NULL DEFAULT '',
+    display_name TEXT NOT NULL DEFAULT '',
+    capacity INTEGER NOT NULL DEFAULT 1,
+    timezone VARCHAR(64) NOT NULL DEFAULT 'UTC',
+    attributes JSONB NOT NULL DEFAULT '{}',
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+
+    CONSTRAINT resources_pkey PRIMARY KEY (id),
+    CONSTRAINT resources_capacity_check CHECK (capacity > 0)
+);
+CREATE INDEX resources_resource_type_idx ON rsvp.resources (resource_type);
+
+-- resources already referenced by reservations become part of the catalog
+INSERT INTO rsvp.resources (id) SELECT DISTINCT resource_id FROM rsvp.reservations;
+
+ALTER TABLE rsvp.reservations
+    ADD CONSTRAINT reservations_resource_id_fkey FOREIGN KEY (resource_id)
+    REFERENCES rsvp.resources (id) ON UPDATE CASCADE ON DELETE RESTRICT;
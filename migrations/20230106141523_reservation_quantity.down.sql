@@ -0,0 +1,9 @@
+-- Add down migration script here
+ALTER TABLE rsvp.reservations DROP CONSTRAINT reservations_conflict;
+ALTER TABLE rsvp.reservations
+    ADD CONSTRAINT reservations_conflict EXCLUDE USING gist (resource_id WITH =, timespan WITH &&);
+
+ALTER TABLE rsvp.reservations
+    DROP CONSTRAINT reservations_quantity_check,
+    DROP COLUMN shared,
+    DROP COLUMN quantity;
@@ -0,0 +1,11 @@
+-- Add up migration script here
+ALTER TABLE rsvp.reservations
+    ADD COLUMN quantity INTEGER NOT NULL DEFAULT 1,
+    -- reservations on resources with capacity > 1 may overlap, their capacity is checked by the manager
+    ADD COLUMN shared BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD CONSTRAINT reservations_quantity_check CHECK (quantity > 0);
+
+ALTER TABLE rsvp.reservations DROP CONSTRAINT reservations_conflict;
+ALTER TABLE rsvp.reservations
+    ADD CONSTRAINT reservations_conflict EXCLUDE USING gist (resource_id WITH =, timespan WITH &&)
+    WHERE (NOT shared);
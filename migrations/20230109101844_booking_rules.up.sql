@@ -0,0 +1,23 @@
+-- Add up migration script here
+-- booking policies for a resource, or for all resources of a type. 0 means no limit
+CREATE TABLE rsvp.booking_rules (
+    id BIGSERIAL NOT NULL,
+    resource_id VARCHAR(64),
+    resource_type VARCHAR(64),
+
+    min_duration_minutes INTEGER NOT NULL DEFAULT 0,
+    max_duration_minutes INTEGER NOT NULL DEFAULT 0,
+    max_lead_time_minutes INTEGER NOT NULL DEFAULT 0,
+    min_notice_minutes INTEGER NOT NULL DEFAULT 0,
+    -- hours of operation in the resource timezone, as minutes from midnight
+    open_minute INTEGER NOT NULL DEFAULT 0,
+    close_minute INTEGER NOT NULL DEFAULT 0,
+    slot_minutes INTEGER NOT NULL DEFAULT 0,
+
+    CONSTRAINT booking_rules_pkey PRIMARY KEY (id),
+    CONSTRAINT booking_rules_resource_id_key UNIQUE (resource_id),
+    CONSTRAINT booking_rules_resource_type_key UNIQUE (resource_type),
+    CONSTRAINT booking_rules_scope_check CHECK ((resource_id IS NULL) <> (resource_type IS NULL)),
+    CONSTRAINT booking_rules_resource_id_fkey FOREIGN KEY (resource_id)
+        REFERENCES rsvp.resources (id) ON UPDATE CASCADE ON DELETE CASCADE
+);
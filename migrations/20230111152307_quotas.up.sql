@@ -0,0 +1,10 @@
+-- Add up migration script here
+-- per user quotas on reservations of a resource type. 0 means no limit
+CREATE TABLE rsvp.quotas (
+    resource_type VARCHAR(64) NOT NULL,
+    max_active INTEGER NOT NULL DEFAULT 0,
+    max_hours_per_week INTEGER NOT NULL DEFAULT 0,
+    max_concurrent INTEGER NOT NULL DEFAULT 0,
+
+    CONSTRAINT quotas_pkey PRIMARY KEY (resource_type)
+);
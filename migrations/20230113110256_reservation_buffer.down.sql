@@ -0,0 +1,12 @@
+-- Add down migration script here
+DROP INDEX rsvp.reservations_padded_timespan_idx;
+ALTER TABLE rsvp.reservations DROP CONSTRAINT reservations_conflict;
+ALTER TABLE rsvp.reservations
+    ADD CONSTRAINT reservations_conflict EXCLUDE USING gist (resource_id WITH =, timespan WITH &&)
+    WHERE (NOT shared);
+ALTER TABLE rsvp.reservations DROP COLUMN padded_timespan;
+
+ALTER TABLE rsvp.resources
+    DROP CONSTRAINT resources_buffer_check,
+    DROP COLUMN buffer_after_minutes,
+    DROP COLUMN buffer_before_minutes;
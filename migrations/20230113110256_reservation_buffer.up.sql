@@ -0,0 +1,16 @@
+-- Add up migration script here
+ALTER TABLE rsvp.resources
+    ADD COLUMN buffer_before_minutes INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN buffer_after_minutes INTEGER NOT NULL DEFAULT 0,
+    ADD CONSTRAINT resources_buffer_check CHECK (buffer_before_minutes >= 0 AND buffer_after_minutes >= 0);
+
+-- timespan extended by the buffers of the resource, reservations conflict on the padded timespan
+ALTER TABLE rsvp.reservations ADD COLUMN padded_timespan TSTZRANGE;
+UPDATE rsvp.reservations SET padded_timespan = timespan;
+ALTER TABLE rsvp.reservations ALTER COLUMN padded_timespan SET NOT NULL;
+
+ALTER TABLE rsvp.reservations DROP CONSTRAINT reservations_conflict;
+ALTER TABLE rsvp.reservations
+    ADD CONSTRAINT reservations_conflict EXCLUDE USING gist (resource_id WITH =, padded_timespan WITH &&)
+    WHERE (NOT shared);
+CREATE INDEX reservations_padded_timespan_idx ON rsvp.reservations USING gist (resource_id, padded_timespan);
@@ -0,0 +1,10 @@
+-- Add down migration script here
+-- postgres could not drop an enum value, move expired reservations out before rebuilding the type
+DELETE FROM rsvp.reservations WHERE status = 'expired';
+ALTER TYPE rsvp.reservation_status RENAME TO reservation_status_old;
+CREATE TYPE rsvp.reservation_status AS ENUM ('unknown', 'pending', 'confirmed', 'blocked');
+ALTER TABLE rsvp.reservations ALTER COLUMN status DROP DEFAULT;
+ALTER TABLE rsvp.reservations
+    ALTER COLUMN status TYPE rsvp.reservation_status USING status::text::rsvp.reservation_status;
+ALTER TABLE rsvp.reservations ALTER COLUMN status SET DEFAULT 'pending';
+DROP TYPE rsvp.reservation_status_old;
@@ -0,0 +1,3 @@
+-- Add up migration script here
+-- a new enum value could not be used in the same transaction, so it is added separately
+ALTER TYPE rsvp.reservation_status ADD VALUE 'expired';
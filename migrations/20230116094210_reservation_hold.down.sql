@@ -0,0 +1,12 @@
+-- Add down migration script here
+ALTER TABLE rsvp.reservations DROP CONSTRAINT reservations_conflict;
+ALTER TABLE rsvp.reservations
+    ADD CONSTRAINT reservations_conflict EXCLUDE USING gist (resource_id WITH =, padded_timespan WITH &&)
+    WHERE (NOT shared);
+
+DROP INDEX rsvp.reservations_expires_at_idx;
+ALTER TABLE rsvp.reservations DROP COLUMN expires_at;
+
+ALTER TABLE rsvp.resources
+    DROP CONSTRAINT resources_hold_ttl_check,
+    DROP COLUMN hold_ttl_seconds;
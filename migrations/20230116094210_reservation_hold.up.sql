@@ -0,0 +1,15 @@
+-- Add up migration script here
+-- how long a pending reservation holds its slot, 0 means the global default
+ALTER TABLE rsvp.resources
+    ADD COLUMN hold_ttl_seconds INTEGER NOT NULL DEFAULT 0,
+    ADD CONSTRAINT resources_hold_ttl_check CHECK (hold_ttl_seconds >= 0);
+
+-- pending reservations which are not confirmed before expires_at are expired
+ALTER TABLE rsvp.reservations ADD COLUMN expires_at TIMESTAMPTZ;
+CREATE INDEX reservations_expires_at_idx ON rsvp.reservations (expires_at) WHERE status = 'pending';
+
+-- expired reservations no longer hold their slots
+ALTER TABLE rsvp.reservations DROP CONSTRAINT reservations_conflict;
+ALTER TABLE rsvp.reservations
+    ADD CONSTRAINT reservations_conflict EXCLUDE USING gist (resource_id WITH =, padded_timespan WITH &&)
+    WHERE (NOT shared AND status <> 'expired');
@@ -0,0 +1,21 @@
+-- Add up migration script here
+CREATE TABLE rsvp.waitlist (
+    id BIGSERIAL NOT NULL,
+    user_id VARCHAR(64) NOT NULL,
+    resource_id VARCHAR(64) NOT NULL,
+    timespan TSTZRANGE NOT NULL,
+    quantity INTEGER NOT NULL DEFAULT 1,
+    note TEXT,
+    -- set when the entry is promoted to a reservation
+    reservation_id BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    promoted_at TIMESTAMPTZ,
+
+    CONSTRAINT waitlist_pkey PRIMARY KEY (id),
+    CONSTRAINT waitlist_quantity_check CHECK (quantity > 0),
+    CONSTRAINT waitlist_resource_id_fkey FOREIGN KEY (resource_id)
+        REFERENCES rsvp.resources (id) ON UPDATE CASCADE ON DELETE CASCADE
+);
+-- entries are promoted first come, first served
+CREATE INDEX waitlist_waiting_idx ON rsvp.waitlist (resource_id, id) WHERE reservation_id IS NULL;
+CREATE INDEX waitlist_user_id_idx ON rsvp.waitlist (user_id);
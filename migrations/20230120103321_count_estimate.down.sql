@@ -0,0 +1,2 @@
+-- Add down migration script here
+DROP FUNCTION rsvp.count_estimate(TEXT);
@@ -0,0 +1,10 @@
+-- Add up migration script here
+-- estimated row count of a query, from the planner statistics (pg_class.reltuples and pg_statistic)
+CREATE OR REPLACE FUNCTION rsvp.count_estimate(query TEXT) RETURNS BIGINT AS $$
+DECLARE
+    plan JSONB;
+BEGIN
+    EXECUTE 'EXPLAIN (FORMAT JSON) ' || query INTO plan;
+    RETURN (plan -> 0 -> 'Plan' ->> 'Plan Rows')::BIGINT;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,10 @@
+-- Add down migration script here
+DROP INDEX rsvp.reservations_resource_status_start_idx;
+DROP INDEX rsvp.reservations_user_status_start_idx;
+DROP INDEX rsvp.reservations_status_created_at_idx;
+DROP INDEX rsvp.reservations_status_resource_id_idx;
+DROP INDEX rsvp.reservations_status_user_id_idx;
+DROP INDEX rsvp.reservations_status_end_idx;
+DROP INDEX rsvp.reservations_status_start_idx;
+
+ALTER TABLE rsvp.reservations DROP COLUMN created_at;
@@ -0,0 +1,12 @@
+-- Add up migration script here
+ALTER TABLE rsvp.reservations ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+-- keyset pagination on (sort key, id) for each sort key, status is always filtered
+CREATE INDEX reservations_status_start_idx ON rsvp.reservations (status, lower(timespan), id);
+CREATE INDEX reservations_status_end_idx ON rsvp.reservations (status, upper(timespan), id);
+CREATE INDEX reservations_status_user_id_idx ON rsvp.reservations (status, user_id, id);
+CREATE INDEX reservations_status_resource_id_idx ON rsvp.reservations (status, resource_id, id);
+CREATE INDEX reservations_status_created_at_idx ON rsvp.reservations (status, created_at, id);
+-- "my upcoming reservations" and the schedule of a resource
+CREATE INDEX reservations_user_status_start_idx ON rsvp.reservations (user_id, status, lower(timespan), id);
+CREATE INDEX reservations_resource_status_start_idx ON rsvp.reservations (resource_id, status, lower(timespan), id);
@@ -0,0 +1,5 @@
+-- Add up migration script here
+-- full-text search on the note of reservations, the expression must match the filter sql
+CREATE INDEX reservations_note_search_idx ON rsvp.reservations USING gin (to_tsvector('english', coalesce(note, '')));
+-- time bounds of the filter
+CREATE INDEX reservations_timespan_idx ON rsvp.reservations USING gist (timespan);
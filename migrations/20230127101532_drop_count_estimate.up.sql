@@ -0,0 +1,3 @@
+-- Add up migration script here
+-- filters bind their values, estimates are explained with the binds by the manager
+DROP FUNCTION rsvp.count_estimate(TEXT);
@@ -0,0 +1,88 @@
+-- Add down migration script here
+DROP POLICY tenant_isolation ON rsvp.quotas;
+DROP POLICY tenant_isolation ON rsvp.booking_rules;
+DROP POLICY tenant_isolation ON rsvp.waitlist;
+DROP POLICY tenant_isolation ON rsvp.reservation_changes;
+DROP POLICY tenant_isolation ON rsvp.reservations;
+DROP POLICY tenant_isolation ON rsvp.resources;
+
+ALTER TABLE rsvp.quotas NO FORCE ROW LEVEL SECURITY, DISABLE ROW LEVEL SECURITY;
+ALTER TABLE rsvp.booking_rules NO FORCE ROW LEVEL SECURITY, DISABLE ROW LEVEL SECURITY;
+ALTER TABLE rsvp.waitlist NO FORCE ROW LEVEL SECURITY, DISABLE ROW LEVEL SECURITY;
+ALTER TABLE rsvp.reservation_changes NO FORCE ROW LEVEL SECURITY, DISABLE ROW LEVEL SECURITY;
+ALTER TABLE rsvp.reservations NO FORCE ROW LEVEL SECURITY, DISABLE ROW LEVEL SECURITY;
+ALTER TABLE rsvp.resources NO FORCE ROW LEVEL SECURITY, DISABLE ROW LEVEL SECURITY;
+
+DROP FUNCTION rsvp.tenant_visible(VARCHAR);
+
+CREATE OR REPLACE FUNCTION rsvp.reservations_trigger() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        -- update reservation_changes
+        INSERT INTO rsvp.reservation_changes (reservation_id, op) VALUES (NEW.id, 'create');
+    ELSIF TG_OP = 'UPDATE' THEN
+        -- if status changed, update reservation_changes
+        IF OLD.status <> NEW.status THEN
+            INSERT INTO rsvp.reservation_changes (reservation_id, op) VALUES (NEW.id, 'update');
+        END IF;
+    ELSIF TG_OP = 'DELETE' THEN
+        -- update reservation_changes
+        INSERT INTO rsvp.reservation_changes (reservation_id, op) VALUES (OLD.id, 'delete');
+    END IF;
+    -- notify a channel called reservation_update
+    NOTIFY reservation_update;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP INDEX rsvp.reservation_changes_tenant_id_idx;
+DROP INDEX rsvp.reservations_user_id_idx;
+DROP INDEX rsvp.reservations_resource_id_idx;
+CREATE INDEX reservations_resource_id_idx ON rsvp.reservations (resource_id);
+CREATE INDEX reservations_user_id_idx ON rsvp.reservations (user_id);
+
+ALTER TABLE rsvp.reservations DROP CONSTRAINT reservations_conflict;
+ALTER TABLE rsvp.reservations
+    ADD CONSTRAINT reservations_conflict EXCLUDE USING gist (resource_id WITH =, padded_timespan WITH &&)
+    WHERE (NOT shared AND status <> 'expired');
+
+-- rows of other tenants could not be kept once ids are global again
+DELETE FROM rsvp.reservations WHERE tenant_id <> '';
+DELETE FROM rsvp.resources WHERE tenant_id <> '';
+DELETE FROM rsvp.booking_rules WHERE tenant_id <> '';
+DELETE FROM rsvp.quotas WHERE tenant_id <> '';
+DELETE FROM rsvp.reservation_changes WHERE tenant_id <> '';
+
+ALTER TABLE rsvp.quotas DROP CONSTRAINT quotas_pkey;
+ALTER TABLE rsvp.quotas ADD CONSTRAINT quotas_pkey PRIMARY KEY (resource_type);
+
+ALTER TABLE rsvp.booking_rules
+    DROP CONSTRAINT booking_rules_resource_id_fkey,
+    DROP CONSTRAINT booking_rules_resource_id_key,
+    DROP CONSTRAINT booking_rules_resource_type_key,
+    ADD CONSTRAINT booking_rules_resource_id_key UNIQUE (resource_id),
+    ADD CONSTRAINT booking_rules_resource_type_key UNIQUE (resource_type);
+ALTER TABLE rsvp.waitlist DROP CONSTRAINT waitlist_resource_id_fkey;
+ALTER TABLE rsvp.reservations DROP CONSTRAINT reservations_resource_id_fkey;
+
+DROP INDEX rsvp.resources_resource_type_idx;
+CREATE INDEX resources_resource_type_idx ON rsvp.resources (resource_type);
+ALTER TABLE rsvp.resources DROP CONSTRAINT resources_pkey;
+ALTER TABLE rsvp.resources ADD CONSTRAINT resources_pkey PRIMARY KEY (id);
+
+ALTER TABLE rsvp.reservations
+    ADD CONSTRAINT reservations_resource_id_fkey FOREIGN KEY (resource_id)
+    REFERENCES rsvp.resources (id) ON UPDATE CASCADE ON DELETE RESTRICT;
+ALTER TABLE rsvp.waitlist
+    ADD CONSTRAINT waitlist_resource_id_fkey FOREIGN KEY (resource_id)
+    REFERENCES rsvp.resources (id) ON UPDATE CASCADE ON DELETE CASCADE;
+ALTER TABLE rsvp.booking_rules
+    ADD CONSTRAINT booking_rules_resource_id_fkey FOREIGN KEY (resource_id)
+    REFERENCES rsvp.resources (id) ON UPDATE CASCADE ON DELETE CASCADE;
+
+ALTER TABLE rsvp.quotas DROP COLUMN tenant_id;
+ALTER TABLE rsvp.booking_rules DROP COLUMN tenant_id;
+ALTER TABLE rsvp.waitlist DROP COLUMN tenant_id;
+ALTER TABLE rsvp.reservation_changes DROP COLUMN tenant_id;
+ALTER TABLE rsvp.reservations DROP COLUMN tenant_id;
+ALTER TABLE rsvp.resources DROP COLUMN tenant_id;
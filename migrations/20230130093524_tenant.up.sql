@@ -0,0 +1,93 @@
+-- Add up migration script here
+-- every row belongs to a tenant, resource ids are unique within a tenant. Existing rows
+-- belong to the default tenant ''
+ALTER TABLE rsvp.resources ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT '';
+ALTER TABLE rsvp.reservations ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT '';
+ALTER TABLE rsvp.reservation_changes ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT '';
+ALTER TABLE rsvp.waitlist ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT '';
+ALTER TABLE rsvp.booking_rules ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT '';
+ALTER TABLE rsvp.quotas ADD COLUMN tenant_id VARCHAR(64) NOT NULL DEFAULT '';
+
+ALTER TABLE rsvp.reservations DROP CONSTRAINT reservations_resource_id_fkey;
+ALTER TABLE rsvp.waitlist DROP CONSTRAINT waitlist_resource_id_fkey;
+ALTER TABLE rsvp.booking_rules DROP CONSTRAINT booking_rules_resource_id_fkey;
+
+ALTER TABLE rsvp.resources DROP CONSTRAINT resources_pkey;
+ALTER TABLE rsvp.resources ADD CONSTRAINT resources_pkey PRIMARY KEY (tenant_id, id);
+DROP INDEX rsvp.resources_resource_type_idx;
+CREATE INDEX resources_resource_type_idx ON rsvp.resources (tenant_id, resource_type);
+
+ALTER TABLE rsvp.reservations
+    ADD CONSTRAINT reservations_resource_id_fkey FOREIGN KEY (tenant_id, resource_id)
+    REFERENCES rsvp.resources (tenant_id, id) ON UPDATE CASCADE ON DELETE RESTRICT;
+ALTER TABLE rsvp.waitlist
+    ADD CONSTRAINT waitlist_resource_id_fkey FOREIGN KEY (tenant_id, resource_id)
+    REFERENCES rsvp.resources (tenant_id, id) ON UPDATE CASCADE ON DELETE CASCADE;
+
+-- MATCH SIMPLE skips the check for rules of a resource type, whose resource_id is NULL
+ALTER TABLE rsvp.booking_rules
+    DROP CONSTRAINT booking_rules_resource_id_key,
+    DROP CONSTRAINT booking_rules_resource_type_key,
+    ADD CONSTRAINT booking_rules_resource_id_key UNIQUE (tenant_id, resource_id),
+    ADD CONSTRAINT booking_rules_resource_type_key UNIQUE (tenant_id, resource_type),
+    ADD CONSTRAINT booking_rules_resource_id_fkey FOREIGN KEY (tenant_id, resource_id)
+        REFERENCES rsvp.resources (tenant_id, id) ON UPDATE CASCADE ON DELETE CASCADE;
+
+ALTER TABLE rsvp.quotas DROP CONSTRAINT quotas_pkey;
+ALTER TABLE rsvp.quotas ADD CONSTRAINT quotas_pkey PRIMARY KEY (tenant_id, resource_type);
+
+ALTER TABLE rsvp.reservations DROP CONSTRAINT reservations_conflict;
+ALTER TABLE rsvp.reservations
+    ADD CONSTRAINT reservations_conflict
+    EXCLUDE USING gist (tenant_id WITH =, resource_id WITH =, padded_timespan WITH &&)
+    WHERE (NOT shared AND status <> 'expired');
+
+DROP INDEX rsvp.reservations_resource_id_idx;
+DROP INDEX rsvp.reservations_user_id_idx;
+CREATE INDEX reservations_resource_id_idx ON rsvp.reservations (tenant_id, resource_id);
+CREATE INDEX reservations_user_id_idx ON rsvp.reservations (tenant_id, user_id);
+CREATE INDEX reservation_changes_tenant_id_idx ON rsvp.reservation_changes (tenant_id, id);
+
+-- listeners are notified with the tenant of the change, and read the changes of their tenant
+CREATE OR REPLACE FUNCTION rsvp.reservations_trigger() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        INSERT INTO rsvp.reservation_changes (reservation_id, op, tenant_id)
+            VALUES (NEW.id, 'create', NEW.tenant_id);
+        PERFORM pg_notify('reservation_update', NEW.tenant_id);
+    ELSIF TG_OP = 'UPDATE' THEN
+        -- if status changed, update reservation_changes
+        IF OLD.status <> NEW.status THEN
+            INSERT INTO rsvp.reservation_changes (reservation_id, op, tenant_id)
+                VALUES (NEW.id, 'update', NEW.tenant_id);
+            PERFORM pg_notify('reservation_update', NEW.tenant_id);
+        END IF;
+    ELSIF TG_OP = 'DELETE' THEN
+        INSERT INTO rsvp.reservation_changes (reservation_id, op, tenant_id)
+            VALUES (OLD.id, 'delete', OLD.tenant_id);
+        PERFORM pg_notify('reservation_update', OLD.tenant_id);
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- defense in depth: transactions of a tenant set rsvp.tenant_id and only see its rows.
+-- Without the setting, e.g. for the hold reaper and migrations, all rows are visible.
+-- Superusers bypass row level security
+CREATE FUNCTION rsvp.tenant_visible(tenant_id VARCHAR) RETURNS BOOLEAN AS $$
+    SELECT coalesce(current_setting('rsvp.tenant_id', true), '') IN ('', tenant_id);
+$$ LANGUAGE sql STABLE;
+
+ALTER TABLE rsvp.resources ENABLE ROW LEVEL SECURITY, FORCE ROW LEVEL SECURITY;
+ALTER TABLE rsvp.reservations ENABLE ROW LEVEL SECURITY, FORCE ROW LEVEL SECURITY;
+ALTER TABLE rsvp.reservation_changes ENABLE ROW LEVEL SECURITY, FORCE ROW LEVEL SECURITY;
+ALTER TABLE rsvp.waitlist ENABLE ROW LEVEL SECURITY, FORCE ROW LEVEL SECURITY;
+ALTER TABLE rsvp.booking_rules ENABLE ROW LEVEL SECURITY, FORCE ROW LEVEL SECURITY;
+ALTER TABLE rsvp.quotas ENABLE ROW LEVEL SECURITY, FORCE ROW LEVEL SECURITY;
+
+CREATE POLICY tenant_isolation ON rsvp.resources USING (rsvp.tenant_visible(tenant_id));
+CREATE POLICY tenant_isolation ON rsvp.reservations USING (rsvp.tenant_visible(tenant_id));
+CREATE POLICY tenant_isolation ON rsvp.reservation_changes USING (rsvp.tenant_visible(tenant_id));
+CREATE POLICY tenant_isolation ON rsvp.waitlist USING (rsvp.tenant_visible(tenant_id));
+CREATE POLICY tenant_isolation ON rsvp.booking_rules USING (rsvp.tenant_visible(tenant_id));
+CREATE POLICY tenant_isolation ON rsvp.quotas USING (rsvp.tenant_visible(tenant_id));
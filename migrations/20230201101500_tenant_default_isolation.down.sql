@@ -0,0 +1,4 @@
+-- Add down migration script here
+CREATE OR REPLACE FUNCTION rsvp.tenant_visible(tenant_id VARCHAR) RETURNS BOOLEAN AS $$
+    SELECT coalesce(current_setting('rsvp.tenant_id', true), '') IN ('', tenant_id);
+$$ LANGUAGE sql STABLE;
@@ -0,0 +1,8 @@
+-- Add up migration script here
+-- the default tenant '' only sees its own rows, like any other tenant. Statements across
+-- tenants, i.e. of the hold reaper, set rsvp.all_tenants instead, which tenant ids never
+-- go into. Without either setting, only the rows of the default tenant are visible
+CREATE OR REPLACE FUNCTION rsvp.tenant_visible(tenant_id VARCHAR) RETURNS BOOLEAN AS $$
+    SELECT coalesce(current_setting('rsvp.all_tenants', true), '') = 'on'
+        OR coalesce(current_setting('rsvp.tenant_id', true), '') = tenant_id;
+$$ LANGUAGE sql STABLE;
@@ -0,0 +1,16 @@
+-- Add down migration script here
+DROP INDEX rsvp.reservations_resource_status_start_idx;
+DROP INDEX rsvp.reservations_user_status_start_idx;
+DROP INDEX rsvp.reservations_status_created_at_idx;
+DROP INDEX rsvp.reservations_status_resource_id_idx;
+DROP INDEX rsvp.reservations_status_user_id_idx;
+DROP INDEX rsvp.reservations_status_end_idx;
+DROP INDEX rsvp.reservations_status_start_idx;
+
+CREATE INDEX reservations_status_start_idx ON rsvp.reservations (status, lower(timespan), id);
+CREATE INDEX reservations_status_end_idx ON rsvp.reservations (status, upper(timespan), id);
+CREATE INDEX reservations_status_user_id_idx ON rsvp.reservations (status, user_id, id);
+CREATE INDEX reservations_status_resource_id_idx ON rsvp.reservations (status, resource_id, id);
+CREATE INDEX reservations_status_created_at_idx ON rsvp.reservations (status, created_at, id);
+CREATE INDEX reservations_user_status_start_idx ON rsvp.reservations (user_id, status, lower(timespan), id);
+CREATE INDEX reservations_resource_status_start_idx ON rsvp.reservations (resource_id, status, lower(timespan), id);
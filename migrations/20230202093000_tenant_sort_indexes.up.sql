@@ -0,0 +1,17 @@
+-- Add up migration script here
+-- keyset pagination within a tenant, every filter and query starts with the tenant
+DROP INDEX rsvp.reservations_status_start_idx;
+DROP INDEX rsvp.reservations_status_end_idx;
+DROP INDEX rsvp.reservations_status_user_id_idx;
+DROP INDEX rsvp.reservations_status_resource_id_idx;
+DROP INDEX rsvp.reservations_status_created_at_idx;
+DROP INDEX rsvp.reservations_user_status_start_idx;
+DROP INDEX rsvp.reservations_resource_status_start_idx;
+
+CREATE INDEX reservations_status_start_idx ON rsvp.reservations (tenant_id, status, lower(timespan), id);
+CREATE INDEX reservations_status_end_idx ON rsvp.reservations (tenant_id, status, upper(timespan), id);
+CREATE INDEX reservations_status_user_id_idx ON rsvp.reservations (tenant_id, status, user_id, id);
+CREATE INDEX reservations_status_resource_id_idx ON rsvp.reservations (tenant_id, status, resource_id, id);
+CREATE INDEX reservations_status_created_at_idx ON rsvp.reservations (tenant_id, status, created_at, id);
+CREATE INDEX reservations_user_status_start_idx ON rsvp.reservations (tenant_id, user_id, status, lower(timespan), id);
+CREATE INDEX reservations_resource_status_start_idx ON rsvp.reservations (tenant_id, resource_id, status, lower(timespan), id);
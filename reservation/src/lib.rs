@@ -1,22 +1,28 @@
+mod catalog;
 mod manager;
+mod quota;
+mod trace;
+mod waitlist;
 
 use abi::Error;
 use async_trait::async_trait;
 use sqlx::PgPool;
-use tokio::sync::mpsc;
+use std::sync::Arc;
+use tokio::sync::{mpsc, Semaphore};
 
-use abi::ReservationId;
+use abi::{ReservationId, ResourceId, RsvpConfig, TenantId, UserId};
 
 #[async_trait]
 pub trait Rsvp {
     /// make a reservation
     async fn reserve(&self, rsvp: abi::Reservation) -> Result<abi::Reservation, Error>;
-    /// change reservation status (if current status is pending, change it to confirmed)
+    /// change reservation status (if current status is pending, change it to confirmed).
+    /// pending reservations whose hold is over could not be confirmed
     async fn change_status(&self, id: ReservationId) -> Result<abi::Reservation, Error>;
     /// update note
     async fn update_note(&self, id: ReservationId, note: String)
         -> Result<abi::Reservation, Error>;
-    /// delete reservation
+    /// delete reservation, the freed window is given to the waitlist
     async fn delete(&self, id: ReservationId) -> Result<abi::Reservation, Error>;
     /// get reservation by id
     async fn get(&self, id: ReservationId) -> Result<abi::Reservation, Error>;
@@ -30,8 +36,60 @@ pub trait Rsvp {
         &self,
         filter: abi::ReservationFilter,
     ) -> Result<(abi::FilterPager, Vec<abi::Reservation>), Error>;
+    /// expire at most `limit` pending reservations whose hold is over, the longest overdue
+    /// first. The freed windows are given to the waitlist
+    async fn expire_holds(&self, limit: i64) -> Result<Vec<abi::Reservation>, Error>;
+    /// queue for a window, the entry is promoted to a pending reservation once the window
+    /// is available, right away if it is already
+    async fn join_waitlist(&self, entry: abi::WaitlistEntry) -> Result<abi::WaitlistEntry, Error>;
+    /// get the quota of a resource type and the current usage of the user
+    async fn get_quota(
+        &self,
+        user_id: UserId,
+        resource_type: String,
+    ) -> Result<(abi::Quota, abi::QuotaUsage), Error>;
+    /// utilization of resources in time buckets
+    async fn stats(&self, query: abi::StatsQuery) -> Result<Vec<abi::ResourceStats>, Error>;
+    /// reservations created, updated or deleted from now on. Deleted reservations only have
+    /// their id
+    async fn listen(&self) -> mpsc::Receiver<Result<abi::Reservation, Error>>;
+}
+
+#[async_trait]
+pub trait ResourceCatalog {
+    /// add a new bookable resource
+    async fn create_resource(&self, resource: abi::Resource) -> Result<abi::Resource, Error>;
+    /// update type, name, capacity, timezone, attributes or active flag of a resource
+    async fn update_resource(&self, resource: abi::Resource) -> Result<abi::Resource, Error>;
+    /// get resource by id
+    async fn get_resource(&self, id: ResourceId) -> Result<abi::Resource, Error>;
+    /// delete a resource which has no reservations
+    async fn delete_resource(&self, id: ResourceId) -> Result<abi::Resource, Error>;
+    /// list resources order by id
+    async fn list_resources(
+        &self,
+        resource_type: String,
+        include_inactive: bool,
+    ) -> Result<Vec<abi::Resource>, Error>;
+    /// set booking rules for a resource or a resource type
+    async fn set_booking_rules(&self, rules: abi::BookingRules)
+        -> Result<abi::BookingRules, Error>;
+    /// get booking rules of a resource or a resource type
+    async fn get_booking_rules(
+        &self,
+        resource_id: ResourceId,
+        resource_type: String,
+    ) -> Result<abi::BookingRules, Error>;
+    /// set per user quota for a resource type
+    async fn set_quota(&self, quota: abi::Quota) -> Result<abi::Quota, Error>;
 }
 
+/// reservations and resources of one tenant, see `with_tenant`
+#[derive(Clone)]
 pub struct ReservationManager {
     pub pool: PgPool,
+    pub config: RsvpConfig,
+    pub tenant_id: TenantId,
+    /// one permit for each running streaming query
+    query_permits: Arc<Semaphore>,
 }
@@ -1,17 +1,86 @@
-use std::collections::VecDeque;
+use std::{collections::VecDeque, ops::Bound};
 
-use abi::{DbConfig, Normalize, ToSql, Validator};
+use abi::{
+    convert_to_timestamp, convert_to_utc_time, DbConfig, Normalize, RsvpConfig, ToSql, Validator,
+};
 use async_trait::async_trait;
-use tracing::{info, warn};
-
-use crate::{Error, ReservationId, ReservationManager, Rsvp};
+use chrono::{DateTime, Utc};
+use prost_types::Timestamp;
+use tracing::{debug, warn, Instrument};
+
+use crate::{
+    quota::{check_quota, quota_usage},
+    trace::Traced,
+    waitlist::promote_waitlist,
+    Error, ReservationId, ReservationManager, Rsvp, TenantId, UserId,
+};
 use futures::StreamExt;
-use sqlx::{postgres::PgPoolOptions, Either, PgPool, Row};
-use tokio::sync::mpsc::{self};
+use sqlx::{
+    migrate::Migrator,
+    postgres::{types::PgRange, PgListener, PgPoolOptions},
+    types::JsonValue,
+    Either, PgPool, Postgres, QueryBuilder, Row, Transaction,
+};
+use std::{sync::Arc, time::Duration};
+use tokio::{
+    sync::{mpsc, Semaphore},
+    time,
+};
+
+/// channel notified with the tenant of each reservation change
+const CHANGES_CHANNEL: &str = "reservation_update";
+
+/// migrations the queries of this build rely on
+static MIGRATOR: Migrator = sqlx::migrate!("../migrations");
 
 impl ReservationManager {
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self::with_pool_and_config(pool, RsvpConfig::default())
+    }
+
+    pub fn with_config(self, config: RsvpConfig) -> Self {
+        Self::with_pool_and_config(self.pool, config).with_tenant(self.tenant_id)
+    }
+
+    fn with_pool_and_config(pool: PgPool, config: RsvpConfig) -> Self {
+        Self {
+            pool,
+            query_permits: Arc::new(Semaphore::new(config.max_concurrent_queries)),
+            config,
+            tenant_id: TenantId::default(),
+        }
+    }
+
+    /// the manager of the rows of the tenant, sharing the pool and the query permits
+    pub fn with_tenant(&self, tenant_id: impl Into<TenantId>) -> Self {
+        Self {
+            tenant_id: tenant_id.into(),
+            ..self.clone()
+        }
+    }
+
+    /// the database is reachable and all migrations of this build are applied
+    pub async fn check_health(&self) -> Result<(), Error> {
+        let applied: Vec<i64> =
+            sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success")
+                .fetch_all(Traced(&self.pool))
+                .await?;
+
+        let pending: Vec<i64> = MIGRATOR
+            .iter()
+            .filter(|m| !m.migration_type.is_down_migration() && !applied.contains(&m.version))
+            .map(|m| m.version)
+            .collect();
+        if !pending.is_empty() {
+            return Err(Error::MigrationsPending(pending));
+        }
+
+        Ok(())
+    }
+
+    /// begin a transaction on the rows of the tenant
+    pub(crate) async fn begin(&self) -> Result<Transaction<'static, Postgres>, Error> {
+        begin_tenant(&self.pool, &self.tenant_id).await
     }
 
     pub async fn from_config(config: &DbConfig) -> Result<Self, abi::Error> {
@@ -23,46 +92,195 @@ impl ReservationManager {
 
         Ok(Self::new(pool))
     }
-}
-
-#[async_trait]
-impl Rsvp for ReservationManager {
-    async fn reserve(&self, mut rsvp: abi::Reservation) -> Result<abi::Reservation, Error> {
-        rsvp.validate()?;
 
+    /// check a validated reservation against the rules, quota and capacity of the locked
+    /// resource, and insert it
+    pub(crate) async fn insert_reservation(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        resource: &abi::Resource,
+        mut rsvp: abi::Reservation,
+    ) -> Result<abi::Reservation, Error> {
         let timespan = rsvp.get_timespan();
 
         let status = abi::ReservationStatus::from_i32(rsvp.status)
             .unwrap_or(abi::ReservationStatus::Pending);
 
-        let id = sqlx::query(
-            "INSERT INTO rsvp.reservations (user_id, resource_id, timespan, note, status) VALUES ($1, $2, $3, $4,
-            $5::rsvp.reservation_status) RETURNING id"
+        // rules of the resource take precedence over rules of its type
+        let rules: Option<abi::BookingRules> = sqlx::query_as(
+            "SELECT * FROM rsvp.booking_rules WHERE tenant_id = $3 AND (resource_id = $1 OR resource_type = $2)
+            ORDER BY resource_id NULLS LAST LIMIT 1",
+        )
+        .bind(&resource.id)
+        .bind(&resource.resource_type)
+        .bind(&self.tenant_id)
+        .fetch_optional(Traced(&mut *tx))
+        .await?;
+
+        if let Some(rules) = rules {
+            let start = convert_to_utc_time(rsvp.start.as_ref().unwrap());
+            let end = convert_to_utc_time(rsvp.end.as_ref().unwrap());
+            rules.check(start, end, resource.get_timezone()?, Utc::now())?;
+        }
+
+        let quota: Option<abi::Quota> =
+            sqlx::query_as("SELECT * FROM rsvp.quotas WHERE tenant_id = $1 AND resource_type = $2")
+                .bind(&self.tenant_id)
+                .bind(&resource.resource_type)
+                .fetch_optional(Traced(&mut *tx))
+                .await?;
+
+        if let Some(quota) = quota {
+            check_quota(&mut *tx, &self.tenant_id, &quota, &rsvp).await?;
+        }
+
+        // buffers of existing reservations and of the new one should not overlap either
+        let padded_timespan = resource.pad_timespan(&timespan);
+        let reserved = reserved_quantity(
+            &mut *tx,
+            &self.tenant_id,
+            &resource.id,
+            "padded_timespan",
+            &padded_timespan,
+        )
+        .await?;
+        if reserved + rsvp.quantity as i64 > resource.capacity as i64 {
+            let booked = if resource.has_buffers() {
+                reserved_quantity(
+                    &mut *tx,
+                    &self.tenant_id,
+                    &resource.id,
+                    "timespan",
+                    &timespan,
+                )
+                .await?
+            } else {
+                reserved
+            };
+
+            let msg = if booked + rsvp.quantity as i64 > resource.capacity as i64 {
+                format!(
+                    "resource {} has {}/{} units reserved within [{}, {}), could not reserve {} more",
+                    resource.id,
+                    booked,
+                    resource.capacity,
+                    get_time_string(rsvp.start.as_ref()),
+                    get_time_string(rsvp.end.as_ref()),
+                    rsvp.quantity
+                )
+            } else {
+                format!(
+                    "reservation [{}, {}) on resource {} overlaps the buffer time of other reservations \
+                    ({} minutes before, {} minutes after each reservation)",
+                    get_time_string(rsvp.start.as_ref()),
+                    get_time_string(rsvp.end.as_ref()),
+                    resource.id,
+                    resource.buffer_before_minutes,
+                    resource.buffer_after_minutes
+                )
+            };
+            return Err(Error::ConflictReservation(msg));
+        }
+
+        let hold_ttl = match resource.hold_ttl_seconds {
+            0 => self.config.hold_ttl_seconds as i64,
+            ttl => ttl as i64,
+        };
+        let hold_ttl =
+            (status == abi::ReservationStatus::Pending && hold_ttl > 0).then_some(hold_ttl);
+
+        let row = sqlx::query(
+            "INSERT INTO rsvp.reservations (user_id, resource_id, timespan, padded_timespan, note, status, quantity,
+            shared, expires_at, tenant_id)
+            VALUES ($1, $2, $3, $4, $5, $6::rsvp.reservation_status, $7, $8, now() + $9 * interval '1 second', $10)
+            RETURNING id, expires_at, created_at"
         )
             .bind(rsvp.user_id.clone())
             .bind(rsvp.resource_id.clone())
             .bind(timespan)
+            .bind(padded_timespan)
             .bind(rsvp.note.clone())
             .bind(status.to_string())
-            .fetch_one(&self.pool)
-            .await?.get(0);
+            .bind(rsvp.quantity)
+            .bind(resource.capacity > 1)
+            .bind(hold_ttl)
+            .bind(&self.tenant_id)
+            .fetch_one(Traced(&mut *tx))
+            .await?;
+
+        rsvp.id = row.get("id");
+        rsvp.expires_at = row
+            .get::<Option<DateTime<Utc>>, _>("expires_at")
+            .map(|t| convert_to_timestamp(&t));
+        rsvp.created_at = Some(convert_to_timestamp(&row.get("created_at")));
+
+        Ok(rsvp)
+    }
+}
+
+#[async_trait]
+impl Rsvp for ReservationManager {
+    async fn reserve(&self, mut rsvp: abi::Reservation) -> Result<abi::Reservation, Error> {
+        rsvp.validate()?;
+
+        if rsvp.quantity == 0 {
+            rsvp.quantity = 1;
+        }
+
+        let mut tx = self.begin().await?;
+
+        let resource = match lock_resource(&mut tx, &self.tenant_id, &rsvp.resource_id).await? {
+            Some(resource) if resource.active => resource,
+            _ => return Err(Error::InvalidResourceId(rsvp.resource_id)),
+        };
+
+        // overdue holds are expired right away, so that they don't block the new reservation.
+        // Users on the waitlist go first for the freed slots
+        let expired = expire_overdue_holds(&mut tx, &self.tenant_id, &resource.id).await?;
+        if !expired.is_empty() {
+            promote_waitlist(self, &mut tx, &resource).await?;
+        }
+
+        let rsvp = self.insert_reservation(&mut tx, &resource, rsvp).await?;
 
-        rsvp.id = id;
+        tx.commit().await?;
 
         Ok(rsvp)
     }
 
     async fn change_status(&self, id: ReservationId) -> Result<abi::Reservation, Error> {
         id.validate()?;
-        let rsvp: abi::Reservation = sqlx::query_as(
-            "UPDATE rsvp.reservations SET status = 'confirmed' where id = $1 and status = 'pending'
+        let mut tx = self.begin().await?;
+        let rsvp: Option<abi::Reservation> = sqlx::query_as(
+            "UPDATE rsvp.reservations SET status = 'confirmed', expires_at = NULL
+            WHERE id = $1 AND tenant_id = $2 AND status = 'pending'
+            AND (expires_at IS NULL OR expires_at > now())
             RETURNING *",
         )
         .bind(id)
-        .fetch_one(&self.pool)
+        .bind(&self.tenant_id)
+        .fetch_optional(Traced(&mut tx))
         .await?;
 
-        Ok(rsvp)
+        if let Some(rsvp) = rsvp {
+            tx.commit().await?;
+            return Ok(rsvp);
+        }
+
+        // the hold is over, no matter whether the reaper has expired it or not
+        let expired: Option<i64> = sqlx::query_scalar(
+            "SELECT id FROM rsvp.reservations WHERE id = $1 AND tenant_id = $2
+            AND (status = 'expired' OR (status = 'pending' AND expires_at <= now()))",
+        )
+        .bind(id)
+        .bind(&self.tenant_id)
+        .fetch_optional(Traced(&mut tx))
+        .await?;
+
+        match expired {
+            Some(id) => Err(Error::ReservationExpired(id)),
+            None => Err(Error::NotFound),
+        }
     }
 
     async fn update_note(
@@ -71,69 +289,115 @@ impl Rsvp for ReservationManager {
         note: String,
     ) -> Result<abi::Reservation, Error> {
         id.validate()?;
-        let rsvp: abi::Reservation =
-            sqlx::query_as("UPDATE rsvp.reservations SET note = $1 where id = $2 RETURNING *")
-                .bind(note)
-                .bind(id)
-                .fetch_one(&self.pool)
-                .await?;
+        let mut tx = self.begin().await?;
+        let rsvp: abi::Reservation = sqlx::query_as(
+            "UPDATE rsvp.reservations SET note = $1 where id = $2 AND tenant_id = $3 RETURNING *",
+        )
+        .bind(note)
+        .bind(id)
+        .bind(&self.tenant_id)
+        .fetch_one(Traced(&mut tx))
+        .await?;
+        tx.commit().await?;
 
         Ok(rsvp)
     }
 
     async fn delete(&self, id: ReservationId) -> Result<abi::Reservation, Error> {
         id.validate()?;
-        let rsvp = sqlx::query_as("DELETE FROM rsvp.reservations WHERE id = $1 RETURNING *")
-            .bind(id)
-            .fetch_one(&self.pool)
-            .await?;
+        let mut tx = self.begin().await?;
+
+        // lock the resource before the reservation, in the same order as reserve
+        let rid: String = sqlx::query_scalar(
+            "SELECT resource_id FROM rsvp.reservations WHERE id = $1 AND tenant_id = $2",
+        )
+        .bind(id)
+        .bind(&self.tenant_id)
+        .fetch_one(Traced(&mut tx))
+        .await?;
+        let resource = lock_resource(&mut tx, &self.tenant_id, &rid)
+            .await?
+            .ok_or(Error::ResourceNotFound(rid))?;
+
+        let rsvp: abi::Reservation = sqlx::query_as(
+            "DELETE FROM rsvp.reservations WHERE id = $1 AND tenant_id = $2 RETURNING *",
+        )
+        .bind(id)
+        .bind(&self.tenant_id)
+        .fetch_one(Traced(&mut tx))
+        .await?;
+
+        if rsvp.status != abi::ReservationStatus::Expired as i32 && resource.active {
+            promote_waitlist(self, &mut tx, &resource).await?;
+        }
+
+        tx.commit().await?;
 
         Ok(rsvp)
     }
 
     async fn get(&self, id: ReservationId) -> Result<abi::Reservation, Error> {
         id.validate()?;
+        let mut tx = self.begin().await?;
         let rsvp: abi::Reservation =
-            sqlx::query_as("SELECT * FROM rsvp.reservations WHERE id = $1 ")
+            sqlx::query_as("SELECT * FROM rsvp.reservations WHERE id = $1 AND tenant_id = $2")
                 .bind(id)
-                .fetch_one(&self.pool)
+                .bind(&self.tenant_id)
+                .fetch_one(Traced(&mut tx))
                 .await?;
+        tx.commit().await?;
 
         Ok(rsvp)
     }
 
     async fn query(
         &self,
-        query: abi::ReservationQuery,
+        mut query: abi::ReservationQuery,
     ) -> mpsc::Receiver<Result<abi::Reservation, Error>> {
+        let Ok(permit) = self.query_permits.clone().try_acquire_owned() else {
+            let (tx, rx) = mpsc::channel(1);
+            let max = self.config.max_concurrent_queries;
+            tx.try_send(Err(Error::TooManyQueries(max))).unwrap();
+            return rx;
+        };
+
         let pool = self.pool.clone();
-        let (tx, rx) = mpsc::channel(128);
-
-        tokio::spawn(async move {
-            let sql = query.to_sql();
-            let mut rsvps = sqlx::query_as(&sql).fetch_many(&pool);
-
-            while let Some(ret) = rsvps.next().await {
-                match ret {
-                    Ok(Either::Left(r)) => {
-                        info!("Query result: {:?}", r);
-                    }
-                    Ok(Either::Right(r)) => {
-                        if tx.send(Ok(r)).await.is_err() {
-                            // rx is dropped, so client disconnected
-                            break;
-                        }
-                    }
-                    Err(e) => {
-                        warn!("Query error: {:?}", e);
-                        if tx.send(Err(e.into())).await.is_err() {
-                            // rx is dropped.
-                            break;
-                        }
-                    }
+        let tenant_id = self.tenant_id.clone();
+        let secret = self.config.page_token_secret.clone();
+        let (tx, rx) = mpsc::channel(self.config.query_buffer_size.max(1));
+        let timeout = self.config.query_timeout_seconds;
+
+        let task = async move {
+            let resume_key = match query
+                .normalize()
+                .and_then(|_| query.apply_resume_token(secret.as_bytes()))
+            {
+                Ok(key) => key,
+                Err(e) => {
+                    let _ = tx.send(Err(e)).await;
+                    return;
                 }
+            };
+
+            // the sqlx stream is dropped, so the query cancelled, on whichever comes first
+            let builder = query.resume_query(&tenant_id, resume_key);
+            let stream = stream_query(&pool, &tenant_id, builder, secret.as_bytes(), &tx);
+            let ret = tokio::select! {
+                ret = time::timeout(Duration::from_secs(timeout), stream) => ret,
+                _ = tx.closed() => {
+                    debug!("Query cancelled, client disconnected");
+                    return;
+                }
+            };
+
+            drop(permit);
+            if ret.is_err() {
+                warn!("Query timed out after {timeout} seconds");
+                let _ = tx.send(Err(Error::QueryTimeout(timeout))).await;
             }
-        });
+        };
+        // statements of the stream belong to the call
+        tokio::spawn(task.in_current_span());
 
         rx
     }
@@ -143,24 +407,453 @@ impl Rsvp for ReservationManager {
         mut filter: abi::ReservationFilter,
     ) -> Result<(abi::FilterPager, Vec<abi::Reservation>), Error> {
         filter.normalize()?;
+        let cursor_key = filter.apply_page_token(self.config.page_token_secret.as_bytes())?;
 
-        let sql = filter.to_sql();
-
-        let rsvps: Vec<abi::Reservation> = sqlx::query_as(&sql).fetch_all(&self.pool).await?;
+        let mut tx = self.begin().await?;
+        let rsvps: Vec<abi::Reservation> = filter
+            .page_query(&self.tenant_id, cursor_key.as_deref())
+            .build_query_as()
+            .fetch_all(Traced(&mut tx))
+            .await?;
 
         let mut rsvps: VecDeque<abi::Reservation> = rsvps.into_iter().collect();
 
-        let pager = filter.get_pager(&mut rsvps);
+        let mut pager = filter.get_pager(&mut rsvps, self.config.page_token_secret.as_bytes());
+
+        if filter.include_total {
+            let total = if filter.approximate_total {
+                // estimated from the planner statistics (pg_class.reltuples and pg_statistic)
+                let plan: JsonValue = filter
+                    .total_query(&self.tenant_id, "EXPLAIN (FORMAT JSON) ")
+                    .build()
+                    .fetch_one(Traced(&mut tx))
+                    .await?
+                    .get(0);
+                plan[0]["Plan"]["Plan Rows"].as_f64().unwrap_or_default() as i64
+            } else {
+                let mut builder = filter.total_query(&self.tenant_id, "SELECT COUNT(*) FROM (");
+                builder.push(") t");
+                builder.build().fetch_one(Traced(&mut tx)).await?.get(0)
+            };
+            pager.total = Some(total);
+        }
+        tx.commit().await?;
 
         Ok((pager, rsvps.into_iter().collect()))
     }
+
+    async fn expire_holds(&self, limit: i64) -> Result<Vec<abi::Reservation>, Error> {
+        let mut tx = begin_all_tenants(&self.pool).await?;
+        let overdue: Vec<(String, String, i64)> = sqlx::query_as(
+            "SELECT tenant_id, resource_id, id FROM rsvp.reservations
+            WHERE status = 'pending' AND expires_at <= now()
+            ORDER BY expires_at LIMIT $1",
+        )
+        .bind(limit)
+        .fetch_all(Traced(&mut tx))
+        .await?;
+        tx.commit().await?;
+
+        let mut holds: Vec<((String, String), Vec<i64>)> = vec![];
+        for (tenant_id, rid, id) in overdue {
+            let key = (tenant_id, rid);
+            match holds.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, ids)) => ids.push(id),
+                None => holds.push((key, vec![id])),
+            }
+        }
+
+        // one transaction per resource, so that the waitlist is promoted with the expiration
+        let mut expired = vec![];
+        for ((tenant_id, rid), ids) in holds {
+            let manager = self.with_tenant(tenant_id);
+            let mut tx = manager.begin().await?;
+            let resource = lock_resource(&mut tx, &manager.tenant_id, &rid)
+                .await?
+                .ok_or(Error::ResourceNotFound(rid))?;
+
+            // the holds may have been confirmed or expired since
+            let rsvps: Vec<abi::Reservation> = sqlx::query_as(
+                "UPDATE rsvp.reservations SET status = 'expired'
+                WHERE tenant_id = $1 AND resource_id = $2 AND id = ANY($3)
+                AND status = 'pending' AND expires_at <= now()
+                RETURNING *",
+            )
+            .bind(&manager.tenant_id)
+            .bind(&resource.id)
+            .bind(&ids)
+            .fetch_all(Traced(&mut tx))
+            .await?;
+            if !rsvps.is_empty() && resource.active {
+                promote_waitlist(&manager, &mut tx, &resource).await?;
+            }
+
+            tx.commit().await?;
+            expired.extend(rsvps);
+        }
+
+        Ok(expired)
+    }
+
+    async fn join_waitlist(
+        &self,
+        mut entry: abi::WaitlistEntry,
+    ) -> Result<abi::WaitlistEntry, Error> {
+        entry.validate()?;
+
+        if entry.quantity == 0 {
+            entry.quantity = 1;
+        }
+
+        let mut tx = self.begin().await?;
+        let resource = match lock_resource(&mut tx, &self.tenant_id, &entry.resource_id).await? {
+            Some(resource) if resource.active => resource,
+            _ => return Err(Error::InvalidResourceId(entry.resource_id)),
+        };
+
+        let entry: abi::WaitlistEntry = sqlx::query_as(
+            "INSERT INTO rsvp.waitlist (user_id, resource_id, timespan, quantity, note, tenant_id)
+            VALUES ($1, $2, $3, $4, $5, $6) RETURNING *",
+        )
+        .bind(&entry.user_id)
+        .bind(&entry.resource_id)
+        .bind(entry.get_timespan())
+        .bind(entry.quantity)
+        .bind(&entry.note)
+        .bind(&self.tenant_id)
+        .fetch_one(Traced(&mut tx))
+        .await?;
+
+        // the window may be free already, or freed by overdue holds. Nothing else would
+        // promote the entry then, earlier entries still go first
+        expire_overdue_holds(&mut tx, &self.tenant_id, &resource.id).await?;
+        let promoted = promote_waitlist(self, &mut tx, &resource).await?;
+        let entry = if promoted.is_empty() {
+            entry
+        } else {
+            sqlx::query_as("SELECT * FROM rsvp.waitlist WHERE id = $1 AND tenant_id = $2")
+                .bind(entry.id)
+                .bind(&self.tenant_id)
+                .fetch_one(Traced(&mut tx))
+                .await?
+        };
+        tx.commit().await?;
+
+        Ok(entry)
+    }
+
+    async fn get_quota(
+        &self,
+        user_id: UserId,
+        resource_type: String,
+    ) -> Result<(abi::Quota, abi::QuotaUsage), Error> {
+        if user_id.is_empty() {
+            return Err(Error::InvalidUserId(user_id));
+        }
+
+        let mut tx = self.begin().await?;
+        let quota: Option<abi::Quota> =
+            sqlx::query_as("SELECT * FROM rsvp.quotas WHERE tenant_id = $1 AND resource_type = $2")
+                .bind(&self.tenant_id)
+                .bind(&resource_type)
+                .fetch_optional(Traced(&mut tx))
+                .await?;
+        let quota = quota.unwrap_or_else(|| abi::Quota::new(resource_type));
+
+        let now = Utc::now();
+        let during = PgRange {
+            start: Bound::Included(now),
+            end: Bound::Included(now),
+        };
+        let usage = quota_usage(
+            &mut tx,
+            &self.tenant_id,
+            &user_id,
+            &quota.resource_type,
+            &abi::Quota::week_of(now),
+            &during,
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok((quota, usage))
+    }
+
+    async fn stats(&self, query: abi::StatsQuery) -> Result<Vec<abi::ResourceStats>, Error> {
+        query.validate()?;
+
+        let mut tx = self.begin().await?;
+        let stats = query
+            .to_query(&self.tenant_id)
+            .build_query_as()
+            .fetch_all(Traced(&mut tx))
+            .await?;
+        tx.commit().await?;
+
+        Ok(stats)
+    }
+
+    async fn listen(&self) -> mpsc::Receiver<Result<abi::Reservation, Error>> {
+        let (tx, rx) = mpsc::channel(self.config.query_buffer_size.max(1));
+        // a listener holds a connection until the client disconnects, like a streaming query
+        let Ok(permit) = self.query_permits.clone().try_acquire_owned() else {
+            let max = self.config.max_concurrent_queries;
+            tx.try_send(Err(Error::TooManyQueries(max))).unwrap();
+            return rx;
+        };
+
+        // subscribe before returning, so that no change made after the call is missed
+        let (listener, last) = match subscribe_changes(&self.pool).await {
+            Ok(ret) => ret,
+            Err(e) => {
+                tx.try_send(Err(e)).unwrap();
+                return rx;
+            }
+        };
+
+        let pool = self.pool.clone();
+        let tenant_id = self.tenant_id.clone();
+        let task = async move {
+            let ret = tokio::select! {
+                ret = forward_changes(&pool, &tenant_id, listener, last, &tx) => ret,
+                _ = tx.closed() => {
+                    debug!("Listen cancelled, client disconnected");
+                    Ok(())
+                }
+            };
+
+            drop(permit);
+            if let Err(e) = ret {
+                warn!("Listen error: {:?}", e);
+                let _ = tx.send(Err(e)).await;
+            }
+        };
+        // statements of the stream belong to the call
+        tokio::spawn(task.in_current_span());
+
+        rx
+    }
+}
+
+/// begin a transaction which only sees the rows of the tenant, in addition to the
+/// conditions on `tenant_id` of each query, if row level security applies to the user
+async fn begin_tenant(
+    pool: &PgPool,
+    tenant_id: &str,
+) -> Result<Transaction<'static, Postgres>, Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("SELECT set_config('rsvp.tenant_id', $1, true)")
+        .bind(tenant_id)
+        .execute(Traced(&mut tx))
+        .await?;
+
+    Ok(tx)
+}
+
+/// begin a transaction which sees the rows of every tenant, for the work of the server
+/// across tenants, e.g. the hold reaper
+async fn begin_all_tenants(pool: &PgPool) -> Result<Transaction<'static, Postgres>, Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("SELECT set_config('rsvp.all_tenants', 'on', true)")
+        .execute(Traced(&mut tx))
+        .await?;
+
+    Ok(tx)
+}
+
+/// listen to the reservation changes, returns the listener and the id of the last change
+async fn subscribe_changes(pool: &PgPool) -> Result<(PgListener, i32), Error> {
+    let mut listener = PgListener::connect_with(pool).await?;
+    listener.listen(CHANGES_CHANNEL).await?;
+
+    let mut tx = begin_all_tenants(pool).await?;
+    let last = sqlx::query_scalar("SELECT COALESCE(MAX(id), 0) FROM rsvp.reservation_changes")
+        .fetch_one(Traced(&mut tx))
+        .await?;
+    tx.commit().await?;
+
+    Ok((listener, last))
+}
+
+/// send the reservations changed after `last` in the tenant to `tx` on each notification,
+/// until the client disconnects. The notifications carry the tenant of the change
+async fn forward_changes(
+    pool: &PgPool,
+    tenant_id: &str,
+    mut listener: PgListener,
+    mut last: i32,
+    tx: &mpsc::Sender<Result<abi::Reservation, Error>>,
+) -> Result<(), Error> {
+    loop {
+        let notification = listener.recv().await?;
+        if notification.payload() != tenant_id {
+            continue;
+        }
+
+        let mut conn = begin_tenant(pool, tenant_id).await?;
+        let changes: Vec<(i32, i64, String)> = sqlx::query_as(
+            "SELECT id, reservation_id, op::text FROM rsvp.reservation_changes
+            WHERE tenant_id = $1 AND id > $2 ORDER BY id",
+        )
+        .bind(tenant_id)
+        .bind(last)
+        .fetch_all(Traced(&mut conn))
+        .await?;
+
+        for (id, rsvp_id, op) in changes {
+            last = id;
+            let rsvp = match op.as_str() {
+                "delete" => abi::Reservation {
+                    id: rsvp_id,
+                    ..Default::default()
+                },
+                _ => {
+                    let rsvp = sqlx::query_as(
+                        "SELECT * FROM rsvp.reservations WHERE id = $1 AND tenant_id = $2",
+                    )
+                    .bind(rsvp_id)
+                    .bind(tenant_id)
+                    .fetch_optional(Traced(&mut conn))
+                    .await?;
+                    // deleted since, the deletion is a later change
+                    let Some(rsvp) = rsvp else {
+                        continue;
+                    };
+                    rsvp
+                }
+            };
+
+            if tx.send(Ok(rsvp)).await.is_err() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// send the reservations of the query with their resume tokens, signed with the secret
+async fn stream_query(
+    pool: &PgPool,
+    tenant_id: &str,
+    mut builder: QueryBuilder<'static, Postgres>,
+    secret: &[u8],
+    tx: &mpsc::Sender<Result<abi::Reservation, Error>>,
+) {
+    let mut conn = match begin_tenant(pool, tenant_id).await {
+        Ok(conn) => conn,
+        Err(e) => {
+            let _ = tx.send(Err(e)).await;
+            return;
+        }
+    };
+
+    let mut rsvps = builder
+        .build_query_as::<abi::Reservation>()
+        .fetch_many(Traced(&mut conn));
+
+    while let Some(ret) = rsvps.next().await {
+        match ret {
+            Ok(Either::Left(r)) => {
+                debug!("Query result: {:?}", r);
+            }
+            Ok(Either::Right(mut r)) => {
+                r.resume_token = r.sign_resume_token(secret);
+                if tx.send(Ok(r)).await.is_err() {
+                    // rx is dropped, so client disconnected
+                    break;
+                }
+            }
+            Err(e) => {
+                warn!("Query error: {:?}", e);
+                if tx.send(Err(e.into())).await.is_err() {
+                    // rx is dropped.
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// lock the resource, so that reservations on the same resource are checked one by one
+async fn lock_resource(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: &str,
+    rid: &str,
+) -> Result<Option<abi::Resource>, Error> {
+    let resource = sqlx::query_as(
+        "SELECT * FROM rsvp.resources WHERE tenant_id = $1 AND id = $2 FOR NO KEY UPDATE",
+    )
+    .bind(tenant_id)
+    .bind(rid)
+    .fetch_optional(Traced(tx))
+    .await?;
+
+    Ok(resource)
+}
+
+/// expire the pending reservations on the locked resource whose hold is over
+async fn expire_overdue_holds(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: &str,
+    rid: &str,
+) -> Result<Vec<abi::Reservation>, Error> {
+    let rsvps = sqlx::query_as(
+        "UPDATE rsvp.reservations SET status = 'expired'
+        WHERE tenant_id = $1 AND resource_id = $2 AND status = 'pending' AND expires_at <= now()
+        RETURNING *",
+    )
+    .bind(tenant_id)
+    .bind(rid)
+    .fetch_all(Traced(tx))
+    .await?;
+
+    Ok(rsvps)
+}
+
+/// peak units of the resource reserved at any instant within the timespan, `column` is
+/// the range column to compare with, either `timespan` or `padded_timespan`
+async fn reserved_quantity(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: &str,
+    rid: &str,
+    column: &str,
+    timespan: &PgRange<DateTime<Utc>>,
+) -> Result<i64, Error> {
+    // usage only goes up at the start of a reservation, so the peak is found at one of them
+    let sql = format!(
+        "SELECT COALESCE(MAX(used), 0)::int8 FROM (
+            SELECT SUM(r.quantity) AS used FROM rsvp.reservations r
+            JOIN (
+                SELECT DISTINCT GREATEST(lower({column}), lower($2)) AS at FROM rsvp.reservations
+                WHERE tenant_id = $3 AND resource_id = $1 AND {column} && $2 AND status <> 'expired'
+            ) p ON r.{column} @> p.at
+            WHERE r.tenant_id = $3 AND r.resource_id = $1 AND r.{column} && $2
+                AND r.status <> 'expired'
+            GROUP BY p.at
+        ) usage"
+    );
+    let reserved = sqlx::query_scalar(&sql)
+        .bind(rid)
+        .bind(timespan)
+        .bind(tenant_id)
+        .fetch_one(Traced(tx))
+        .await?;
+
+    Ok(reserved)
+}
+
+fn get_time_string(ts: Option<&Timestamp>) -> String {
+    ts.map(|ts| convert_to_utc_time(ts).to_rfc3339())
+        .unwrap_or_default()
 }
 
 #[cfg(test)]
 mod tests {
 
     use super::*;
-    use abi::{ReservationFilterBuilder, ReservationQueryBuilder, ReservationStatus};
+    use crate::ResourceCatalog;
+    use abi::{
+        ReservationFilter, ReservationFilterBuilder, ReservationQueryBuilder, ReservationStatus,
+    };
     use prost_types::Timestamp;
 
     use sqlx_postgres_tester::TestPg;
@@ -203,87 +896,729 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn change_status_should_work() -> Result<(), Error> {
+    async fn reserve_shared_resource_should_respect_capacity() -> Result<(), Error> {
         let tdb = get_tdb();
-        let pool = tdb.get_pool().await;
-        let (rsvp, manager) = make_alice_reservation(&pool).await;
+        let manager = ReservationManager::new(tdb.get_pool().await);
+        manager
+            .create_resource(abi::Resource::new("lab 301", "lab", "Shared lab 301", 3))
+            .await?;
 
-        let rsvp = manager.change_status(rsvp.id).await?;
+        let make_rsvp = |uid: &str, start: &str, end: &str, quantity: i32| {
+            abi::Reservation::new_pending(
+                uid,
+                "lab 301",
+                start.parse().unwrap(),
+                end.parse().unwrap(),
+                "",
+            )
+            .with_quantity(quantity)
+        };
+
+        manager
+            .reserve(make_rsvp(
+                "alice id",
+                "2023-01-02T09:00:00-0700",
+                "2023-01-02T12:00:00-0700",
+                2,
+            ))
+            .await?;
+        manager
+            .reserve(make_rsvp(
+                "james id",
+                "2023-01-02T11:00:00-0700",
+                "2023-01-02T14:00:00-0700",
+                1,
+            ))
+            .await?;
+        // starts after alice leaves, so only james's seat is taken
+        manager
+            .reserve(make_rsvp(
+                "tyr id",
+                "2023-01-02T12:00:00-0700",
+                "2023-01-02T13:00:00-0700",
+                2,
+            ))
+            .await?;
 
-        assert_eq!(rsvp.status, ReservationStatus::Confirmed as i32);
+        let err = manager
+            .reserve(make_rsvp(
+                "bob id",
+                "2023-01-02T10:00:00-0700",
+                "2023-01-02T11:30:00-0700",
+                1,
+            ))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::ConflictReservation(_)));
+
+        let err = manager
+            .reserve(make_rsvp(
+                "bob id",
+                "2023-01-03T10:00:00-0700",
+                "2023-01-03T11:00:00-0700",
+                4,
+            ))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::ConflictReservation(_)));
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn change_status_not_pending_should_do_nothing() -> Result<(), Error> {
+    async fn reserve_should_respect_buffers() -> Result<(), Error> {
         let tdb = get_tdb();
-        let pool = tdb.get_pool().await;
-        let (rsvp, manager) = make_alice_reservation(&pool).await;
-
-        let rsvp = manager.change_status(rsvp.id).await?;
+        let manager = ReservationManager::new(tdb.get_pool().await);
+        manager
+            .create_resource(
+                abi::Resource::new("room 518", "room", "Room 518", 1).with_buffers(15, 30),
+            )
+            .await?;
 
-        assert_eq!(rsvp.status, ReservationStatus::Confirmed as i32);
+        let make_rsvp = |uid: &str, start: &str, end: &str| {
+            abi::Reservation::new_pending(
+                uid,
+                "room 518",
+                start.parse().unwrap(),
+                end.parse().unwrap(),
+                "",
+            )
+        };
+
+        manager
+            .reserve(make_rsvp(
+                "alice id",
+                "2023-01-02T09:00:00-0700",
+                "2023-01-02T10:00:00-0700",
+            ))
+            .await?;
 
-        let ret = manager.change_status(rsvp.id).await.unwrap_err();
+        // starts within the cleanup time after alice's reservation
+        let err = manager
+            .reserve(make_rsvp(
+                "james id",
+                "2023-01-02T10:15:00-0700",
+                "2023-01-02T11:00:00-0700",
+            ))
+            .await
+            .unwrap_err();
+        let Error::ConflictReservation(msg) = err else {
+            panic!("expect conflict reservation, got {:?}", err);
+        };
+        assert!(msg.contains("buffer time"));
+
+        // the setup time before james's reservation overlaps alice's cleanup time
+        assert!(manager
+            .reserve(make_rsvp(
+                "james id",
+                "2023-01-02T10:40:00-0700",
+                "2023-01-02T11:00:00-0700",
+            ))
+            .await
+            .is_err());
+
+        manager
+            .reserve(make_rsvp(
+                "james id",
+                "2023-01-02T10:45:00-0700",
+                "2023-01-02T11:30:00-0700",
+            ))
+            .await?;
 
-        assert_eq!(ret, Error::NotFound);
+        // the window itself is taken, not only the buffer
+        let err = manager
+            .reserve(make_rsvp(
+                "tyr id",
+                "2023-01-02T09:30:00-0700",
+                "2023-01-02T10:00:00-0700",
+            ))
+            .await
+            .unwrap_err();
+        let Error::ConflictReservation(msg) = err else {
+            panic!("expect conflict reservation, got {:?}", err);
+        };
+        assert!(!msg.contains("buffer time"));
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn update_note_should_work() -> Result<(), Error> {
+    async fn concurrent_reserve_should_not_exceed_capacity() -> Result<(), Error> {
         let tdb = get_tdb();
         let pool = tdb.get_pool().await;
-        let (rsvp, manager) = make_alice_reservation(&pool).await;
+        let manager = ReservationManager::new(pool.clone());
+        manager
+            .create_resource(abi::Resource::new(
+                "parking a",
+                "parking",
+                "Parking lot A",
+                3,
+            ))
+            .await?;
 
-        let rsvp = manager.update_note(rsvp.id, "007".into()).await?;
+        let handles: Vec<_> = (0..10)
+            .map(|i| {
+                let manager = ReservationManager::new(pool.clone());
+                tokio::spawn(async move {
+                    let rsvp = abi::Reservation::new_pending(
+                        format!("user {i}"),
+                        "parking a",
+                        "2023-01-02T09:00:00-0700".parse().unwrap(),
+                        "2023-01-02T18:00:00-0700".parse().unwrap(),
+                        "",
+                    );
+                    manager.reserve(rsvp).await
+                })
+            })
+            .collect();
+
+        let mut reserved = 0;
+        for handle in handles {
+            if handle.await.unwrap().is_ok() {
+                reserved += 1;
+            }
+        }
 
-        assert_eq!(rsvp.note, "007");
+        assert_eq!(reserved, 3);
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn delete_reservation_should_work() -> Result<(), Error> {
+    async fn reserve_should_enforce_booking_rules() -> Result<(), Error> {
         let tdb = get_tdb();
-        let pool = tdb.get_pool().await;
-        let (rsvp, manager) = make_alice_reservation(&pool).await;
+        let manager = ReservationManager::new(tdb.get_pool().await);
+        manager
+            .create_resource(abi::Resource::new("lab 301", "lab", "Shared lab 301", 12))
+            .await?;
+        manager
+            .set_booking_rules(abi::BookingRules {
+                max_duration_minutes: 120,
+                slot_minutes: 30,
+                ..abi::BookingRules::for_resource_type("lab")
+            })
+            .await?;
 
-        manager.delete(rsvp.id).await?;
+        let make_rsvp = |start: &str, end: &str| {
+            abi::Reservation::new_pending(
+                "alice id",
+                "lab 301",
+                start.parse().unwrap(),
+                end.parse().unwrap(),
+                "",
+            )
+        };
+
+        let err = manager
+            .reserve(make_rsvp(
+                "2023-01-02T09:00:00-0700",
+                "2023-01-02T12:00:00-0700",
+            ))
+            .await
+            .unwrap_err();
+        assert_eq!(err, Error::DurationTooLong(180, 120));
+
+        let err = manager
+            .reserve(make_rsvp(
+                "2023-01-02T09:10:00-0700",
+                "2023-01-02T10:00:00-0700",
+            ))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err,
+            Error::MisalignedStart("2023-01-02T16:10:00+00:00".into(), 30)
+        );
 
-        let err = manager.get(rsvp.id).await.unwrap_err();
+        // rules of the resource take precedence over rules of its type
+        manager
+            .set_booking_rules(abi::BookingRules::for_resource("lab 301"))
+            .await?;
+        manager
+            .reserve(make_rsvp(
+                "2023-01-02T09:10:00-0700",
+                "2023-01-02T12:00:00-0700",
+            ))
+            .await?;
 
-        // println!("The error: {:?}", err);
+        Ok(())
+    }
 
-        assert_eq!(err, Error::NotFound);
+    #[tokio::test]
+    async fn reserve_should_enforce_quota() -> Result<(), Error> {
+        let tdb = get_tdb();
+        let manager = ReservationManager::new(tdb.get_pool().await);
+        for rid in ["lab 301", "lab 302", "lab 303"] {
+            manager
+                .create_resource(abi::Resource::new(rid, "lab", rid, 1))
+                .await?;
+        }
+        manager
+            .set_quota(abi::Quota {
+                max_active: 2,
+                max_hours_per_week: 5,
+                ..abi::Quota::new("lab")
+            })
+            .await?;
+
+        let make_rsvp = |rid: &str, start: &str, end: &str| {
+            abi::Reservation::new_pending(
+                "alice id",
+                rid,
+                start.parse().unwrap(),
+                end.parse().unwrap(),
+                "",
+            )
+        };
+
+        // 2099-01-05 is a Monday
+        manager
+            .reserve(make_rsvp(
+                "lab 301",
+                "2099-01-05T09:00:00+0000",
+                "2099-01-05T12:00:00+0000",
+            ))
+            .await?;
+
+        let (quota, usage) = manager.get_quota("alice id".into(), "lab".into()).await?;
+        assert_eq!(quota.remaining_active(&usage), Some(1));
+
+        let err = manager
+            .reserve(make_rsvp(
+                "lab 302",
+                "2099-01-06T09:00:00+0000",
+                "2099-01-06T12:00:00+0000",
+            ))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::QuotaExceeded(_)));
+
+        // next week
+        manager
+            .reserve(make_rsvp(
+                "lab 302",
+                "2099-01-12T09:00:00+0000",
+                "2099-01-12T12:00:00+0000",
+            ))
+            .await?;
+
+        let err = manager
+            .reserve(make_rsvp(
+                "lab 303",
+                "2099-01-20T09:00:00+0000",
+                "2099-01-20T10:00:00+0000",
+            ))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::QuotaExceeded(_)));
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn get_reservation_should_work() -> Result<(), Error> {
+    async fn reserve_unknown_resource_should_rejected() -> Result<(), Error> {
         let tdb = get_tdb();
-        let pool = tdb.get_pool().await;
-        let (rsvp, manager) = make_alice_reservation(&pool).await;
+        let manager = ReservationManager::new(tdb.get_pool().await);
 
-        let rsvp1 = manager.get(rsvp.id).await?;
+        let rsvp = abi::Reservation::new_pending(
+            "alice id",
+            "Ocean view room 999",
+            "2022-12-26T15:00:00-0700".parse().unwrap(),
+            "2022-12-31T00:00:00-0700".parse().unwrap(),
+            "",
+        );
 
-        assert_eq!(rsvp, rsvp1);
+        let err = manager.reserve(rsvp).await.unwrap_err();
+        assert_eq!(err, Error::InvalidResourceId("Ocean view room 999".into()));
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn query_reservations_should_work() -> Result<(), Error> {
+    async fn reserve_inactive_resource_should_rejected() -> Result<(), Error> {
         let tdb = get_tdb();
         let pool = tdb.get_pool().await;
-        let (rsvp, manager) = make_james_reservation(&pool).await;
+        let (rsvp, manager) = make_alice_reservation(&pool).await;
 
-        let query = ReservationQueryBuilder::default()
-            .user_id("james id")
-            .start("2022-10-25T15:00:00-0700".parse::<Timestamp>().unwrap())
+        let mut resource = manager.get_resource(rsvp.resource_id.clone()).await?;
+        resource.active = false;
+        manager.update_resource(resource).await?;
+
+        let rsvp = abi::Reservation::new_pending(
+            "james id",
+            rsvp.resource_id,
+            "2023-01-25T15:00:00-0700".parse().unwrap(),
+            "2023-01-30T00:00:00-0700".parse().unwrap(),
+            "",
+        );
+
+        let err = manager.reserve(rsvp).await.unwrap_err();
+        assert_eq!(err, Error::InvalidResourceId("Ocean view room 518".into()));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn change_status_should_work() -> Result<(), Error> {
+        let tdb = get_tdb();
+        let pool = tdb.get_pool().await;
+        let (rsvp, manager) = make_alice_reservation(&pool).await;
+
+        let rsvp = manager.change_status(rsvp.id).await?;
+
+        assert_eq!(rsvp.status, ReservationStatus::Confirmed as i32);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn change_status_not_pending_should_do_nothing() -> Result<(), Error> {
+        let tdb = get_tdb();
+        let pool = tdb.get_pool().await;
+        let (rsvp, manager) = make_alice_reservation(&pool).await;
+
+        let rsvp = manager.change_status(rsvp.id).await?;
+
+        assert_eq!(rsvp.status, ReservationStatus::Confirmed as i32);
+
+        let ret = manager.change_status(rsvp.id).await.unwrap_err();
+
+        assert_eq!(ret, Error::NotFound);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn expired_hold_should_not_block_or_be_confirmed() -> Result<(), Error> {
+        let tdb = get_tdb();
+        let pool = tdb.get_pool().await;
+        let (rsvp, manager) = make_alice_reservation(&pool).await;
+        assert!(rsvp.expires_at.is_some());
+
+        // the hold is over, but the reaper has not run yet
+        sqlx::query("UPDATE rsvp.reservations SET expires_at = now() WHERE id = $1")
+            .bind(rsvp.id)
+            .execute(&pool)
+            .await?;
+
+        let err = manager.change_status(rsvp.id).await.unwrap_err();
+        assert_eq!(err, Error::ReservationExpired(rsvp.id));
+
+        // the same window could be reserved again
+        make_reservation(
+            &pool,
+            "james id",
+            "Ocean view room 518",
+            "2022-12-25T15:00:00-0700",
+            "2022-12-30T00:00:00-0700",
+            "",
+        )
+        .await;
+
+        let rsvp = manager.get(rsvp.id).await?;
+        assert_eq!(rsvp.status, ReservationStatus::Expired as i32);
+        assert!(manager.expire_holds(10).await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn confirmed_reservation_should_not_expire() -> Result<(), Error> {
+        let tdb = get_tdb();
+        let pool = tdb.get_pool().await;
+        let (rsvp, manager) = make_alice_reservation(&pool).await;
+
+        let rsvp = manager.change_status(rsvp.id).await?;
+        assert_eq!(rsvp.expires_at, None);
+
+        let manager = manager.with_config(RsvpConfig {
+            hold_ttl_seconds: 0,
+            ..RsvpConfig::default()
+        });
+        let rsvp = manager
+            .reserve(abi::Reservation::new_pending(
+                "alice id",
+                "Ocean view room 518",
+                "2023-01-25T15:00:00-0700".parse().unwrap(),
+                "2023-01-28T12:00:00-0700".parse().unwrap(),
+                "",
+            ))
+            .await?;
+        assert_eq!(rsvp.expires_at, None);
+        assert!(manager.expire_holds(10).await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn expire_holds_should_expire_at_most_limit() -> Result<(), Error> {
+        let tdb = get_tdb();
+        let pool = tdb.get_pool().await;
+        let (rsvp, manager) = make_alice_reservation(&pool).await;
+        for day in 2..5 {
+            manager
+                .reserve(abi::Reservation::new_pending(
+                    "alice id",
+                    "Ocean view room 518",
+                    format!("2023-02-0{day}T09:00:00-0700").parse().unwrap(),
+                    format!("2023-02-0{day}T10:00:00-0700").parse().unwrap(),
+                    "",
+                ))
+                .await?;
+        }
+        // the first reservation is the longest overdue
+        sqlx::query(
+            "UPDATE rsvp.reservations SET expires_at = now() - interval '1 hour' WHERE id = $1",
+        )
+        .bind(rsvp.id)
+        .execute(&pool)
+        .await?;
+        sqlx::query("UPDATE rsvp.reservations SET expires_at = now() WHERE id <> $1")
+            .bind(rsvp.id)
+            .execute(&pool)
+            .await?;
+
+        let expired = manager.expire_holds(2).await?;
+        assert_eq!(expired.len(), 2);
+        assert!(expired.iter().any(|r| r.id == rsvp.id));
+        assert_eq!(manager.expire_holds(2).await?.len(), 2);
+        assert!(manager.expire_holds(2).await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn update_note_should_work() -> Result<(), Error> {
+        let tdb = get_tdb();
+        let pool = tdb.get_pool().await;
+        let (rsvp, manager) = make_alice_reservation(&pool).await;
+
+        let rsvp = manager.update_note(rsvp.id, "007".into()).await?;
+
+        assert_eq!(rsvp.note, "007");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn delete_reservation_should_work() -> Result<(), Error> {
+        let tdb = get_tdb();
+        let pool = tdb.get_pool().await;
+        let (rsvp, manager) = make_alice_reservation(&pool).await;
+
+        manager.delete(rsvp.id).await?;
+
+        let err = manager.get(rsvp.id).await.unwrap_err();
+
+        // println!("The error: {:?}", err);
+
+        assert_eq!(err, Error::NotFound);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn get_reservation_should_work() -> Result<(), Error> {
+        let tdb = get_tdb();
+        let pool = tdb.get_pool().await;
+        let (rsvp, manager) = make_alice_reservation(&pool).await;
+
+        let rsvp1 = manager.get(rsvp.id).await?;
+
+        assert_eq!(rsvp, rsvp1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn check_health_should_report_pending_migrations() -> Result<(), Error> {
+        let tdb = get_tdb();
+        let pool = tdb.get_pool().await;
+        let manager = ReservationManager::new(pool.clone());
+        manager.check_health().await?;
+
+        let version: i64 = sqlx::query_scalar(
+            "DELETE FROM _sqlx_migrations WHERE version = (SELECT MAX(version) FROM _sqlx_migrations)
+            RETURNING version",
+        )
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(
+            manager.check_health().await,
+            Err(Error::MigrationsPending(vec![version]))
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn tenants_should_be_isolated() -> Result<(), Error> {
+        let tdb = get_tdb();
+        let pool = tdb.get_pool().await;
+        let manager = ReservationManager::new(pool.clone());
+        let acme = manager.with_tenant("acme");
+        let globex = manager.with_tenant("globex");
+
+        let mut rsvps = vec![];
+        for m in [&acme, &globex] {
+            m.create_resource(abi::Resource::new("room 1", "room", "Room 1", 1))
+                .await?;
+            let rsvp = abi::Reservation::new_pending(
+                "alice id",
+                "room 1",
+                "2022-12-25T15:00:00-0700".parse().unwrap(),
+                "2022-12-28T12:00:00-0700".parse().unwrap(),
+                "same window in another tenant",
+            );
+            rsvps.push(m.reserve(rsvp).await?);
+        }
+
+        assert_eq!(globex.get(rsvps[0].id).await, Err(Error::NotFound));
+        assert_eq!(globex.delete(rsvps[0].id).await, Err(Error::NotFound));
+        assert!(manager.get_resource("room 1".into()).await.is_err());
+
+        let filter = ReservationFilterBuilder::default()
+            .user_id("alice id")
+            .status(ReservationStatus::Pending as i32)
+            .build()
+            .unwrap();
+        let (_, found) = acme.filter(filter).await?;
+        assert_eq!(found, vec![rsvps[0].clone()]);
+
+        let query = ReservationQueryBuilder::default()
+            .resource_id("room 1")
+            .status(ReservationStatus::Pending as i32)
+            .build()
+            .unwrap();
+        let mut rx = globex.query(query).await;
+        assert_eq!(rx.recv().await.unwrap()?.id, rsvps[1].id);
+        assert_eq!(rx.recv().await, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn row_level_security_should_hide_other_tenants() -> Result<(), Error> {
+        let tdb = get_tdb();
+        let pool = tdb.get_pool().await;
+        let manager = ReservationManager::new(pool.clone());
+        let acme = manager.with_tenant("acme");
+        let globex = manager.with_tenant("globex");
+        acme.create_resource(abi::Resource::new("room 1", "room", "Room 1", 1))
+            .await?;
+        acme.reserve(abi::Reservation::new_pending(
+            "alice id",
+            "room 1",
+            "2022-12-25T15:00:00-0700".parse().unwrap(),
+            "2022-12-28T12:00:00-0700".parse().unwrap(),
+            "hidden from globex",
+        ))
+        .await?;
+        globex
+            .create_resource(abi::Resource::new("room 2", "room", "Room 2", 1))
+            .await?;
+        manager
+            .create_resource(abi::Resource::new("room 3", "room", "Room 3", 1))
+            .await?;
+
+        // the test connects as a superuser, which bypasses row level security. The role and
+        // its grants are rolled back with the transaction
+        async fn visible(
+            mut tx: Transaction<'static, Postgres>,
+        ) -> Result<(i64, Vec<String>), Error> {
+            for sql in [
+                "DO $$ BEGIN CREATE ROLE rsvp_rls_test; EXCEPTION WHEN duplicate_object THEN NULL; END $$",
+                "GRANT USAGE ON SCHEMA rsvp TO rsvp_rls_test",
+                "GRANT SELECT ON rsvp.reservations, rsvp.resources TO rsvp_rls_test",
+                "SET LOCAL ROLE rsvp_rls_test",
+            ] {
+                sqlx::query(sql).execute(&mut tx).await?;
+            }
+
+            // no condition on the tenant
+            let rsvps = sqlx::query_scalar("SELECT COUNT(*) FROM rsvp.reservations")
+                .fetch_one(&mut tx)
+                .await?;
+            let resources = sqlx::query_scalar("SELECT id FROM rsvp.resources ORDER BY id")
+                .fetch_all(&mut tx)
+                .await?;
+            Ok((rsvps, resources))
+        }
+
+        assert_eq!(
+            visible(globex.begin().await?).await?,
+            (0, vec!["room 2".into()])
+        );
+        // the default tenant is a tenant like the others
+        assert_eq!(
+            visible(manager.begin().await?).await?,
+            (0, vec!["room 3".into()])
+        );
+        assert_eq!(
+            visible(pool.begin().await?).await?,
+            (0, vec!["room 3".into()])
+        );
+        let all = vec!["room 1".into(), "room 2".into(), "room 3".into()];
+        assert_eq!(visible(begin_all_tenants(&pool).await?).await?, (1, all));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn listen_should_stream_changes_of_tenant() -> Result<(), Error> {
+        let tdb = get_tdb();
+        let pool = tdb.get_pool().await;
+        let manager = ReservationManager::new(pool.clone());
+        let acme = manager.with_tenant("acme");
+        let globex = manager.with_tenant("globex");
+        for m in [&acme, &globex] {
+            m.create_resource(abi::Resource::new("room 1", "room", "Room 1", 1))
+                .await?;
+        }
+
+        let mut rx = acme.listen().await;
+        let new_rsvp = || {
+            abi::Reservation::new_pending(
+                "alice id",
+                "room 1",
+                "2022-12-25T15:00:00-0700".parse().unwrap(),
+                "2022-12-28T12:00:00-0700".parse().unwrap(),
+                "listen",
+            )
+        };
+
+        // changes of other tenants are not sent
+        globex.reserve(new_rsvp()).await?;
+        let rsvp = acme.reserve(new_rsvp()).await?;
+        let received = time::timeout(Duration::from_secs(5), rx.recv()).await;
+        assert_eq!(received.unwrap().unwrap()?.id, rsvp.id);
+
+        let rsvp = acme.change_status(rsvp.id).await?;
+        let received = time::timeout(Duration::from_secs(5), rx.recv()).await;
+        assert_eq!(received.unwrap().unwrap()?, rsvp);
+
+        acme.delete(rsvp.id).await?;
+        let received = time::timeout(Duration::from_secs(5), rx.recv()).await;
+        assert_eq!(
+            received.unwrap().unwrap()?,
+            abi::Reservation {
+                id: rsvp.id,
+                ..Default::default()
+            }
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn query_reservations_should_work() -> Result<(), Error> {
+        let tdb = get_tdb();
+        let pool = tdb.get_pool().await;
+        let (rsvp, manager) = make_james_reservation(&pool).await;
+
+        let query = ReservationQueryBuilder::default()
+            .user_id("james id")
+            .start("2022-10-25T15:00:00-0700".parse::<Timestamp>().unwrap())
             .end("2022-12-31T00:00:00-0700".parse::<Timestamp>().unwrap())
             .status(ReservationStatus::Pending)
             .build()
@@ -291,7 +1626,11 @@ mod tests {
 
         let mut rx = manager.query(query).await;
 
-        assert_eq!(rx.recv().await, Some(Ok(rsvp.clone())));
+        // streamed reservations carry a token to resume after them
+        let streamed = rx.recv().await.unwrap()?;
+        let secret = manager.config.page_token_secret.as_bytes();
+        assert_eq!(streamed.resume_token, rsvp.sign_resume_token(secret));
+        assert_eq!(streamed.id, rsvp.id);
         assert_eq!(rx.recv().await, None);
 
         let query = ReservationQueryBuilder::default()
@@ -309,7 +1648,227 @@ mod tests {
         let rsvp = manager.change_status(rsvp.id).await?;
         let mut rx = manager.query(query).await;
 
-        assert_eq!(rx.recv().await, Some(Ok(rsvp)));
+        let streamed = rx.recv().await.unwrap()?;
+        assert_eq!(
+            abi::Reservation {
+                resume_token: String::new(),
+                ..streamed
+            },
+            rsvp
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn query_should_resume_after_last_received() -> Result<(), Error> {
+        let tdb = get_tdb();
+        let pool = tdb.get_pool().await;
+        let manager = ReservationManager::new(pool.clone());
+        for i in 0..5 {
+            make_reservation(
+                &pool,
+                "james id",
+                &format!("room {i}"),
+                &format!("2030-01-{:02}T15:00:00-0700", 10 + i),
+                "2030-01-20T12:00:00-0700",
+                "",
+            )
+            .await;
+        }
+
+        let query = ReservationQueryBuilder::default()
+            .user_id("james id")
+            .start("2030-01-11T00:00:00-0700".parse::<Timestamp>().unwrap())
+            .end("2030-01-15T00:00:00-0700".parse::<Timestamp>().unwrap())
+            .match_mode(abi::ReservationMatchMode::StartsWithin)
+            .limit(2)
+            .build()
+            .unwrap();
+        let mut rx = manager.query(query.clone()).await;
+        let mut first = vec![];
+        while let Some(rsvp) = rx.recv().await {
+            first.push(rsvp?);
+        }
+        assert_eq!(first.len(), 2);
+
+        // the reservation starting on 2030-01-10 is not within the range
+        let query = abi::ReservationQuery {
+            limit: 0,
+            resume_token: first[1].resume_token.clone(),
+            ..query
+        };
+        let mut rx = manager.query(query).await;
+        let mut rest = vec![];
+        while let Some(rsvp) = rx.recv().await {
+            rest.push(rsvp?);
+        }
+        let resources: Vec<_> = first
+            .iter()
+            .chain(rest.iter())
+            .map(|r| r.resource_id.as_str())
+            .collect();
+        assert_eq!(resources, vec!["room 1", "room 2", "room 3", "room 4"]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn query_should_cap_concurrent_streams() -> Result<(), Error> {
+        let tdb = get_tdb();
+        let pool = tdb.get_pool().await;
+        for i in 0..3 {
+            make_reservation(
+                &pool,
+                "james id",
+                &format!("room {i}"),
+                "2030-01-10T15:00:00-0700",
+                "2030-01-20T12:00:00-0700",
+                "",
+            )
+            .await;
+        }
+        let manager = ReservationManager::new(pool.clone()).with_config(RsvpConfig {
+            query_buffer_size: 1,
+            max_concurrent_queries: 1,
+            ..Default::default()
+        });
+        let query = ReservationQueryBuilder::default()
+            .user_id("james id")
+            .build()
+            .unwrap();
+
+        // the first stream is blocked on its full buffer, and holds the only permit
+        let mut rx = manager.query(query.clone()).await;
+        assert!(rx.recv().await.unwrap().is_ok());
+        let mut rejected = manager.query(query.clone()).await;
+        assert_eq!(rejected.recv().await, Some(Err(Error::TooManyQueries(1))));
+
+        // dropping the stream cancels the query and releases the permit
+        drop(rx);
+        let mut rx = loop {
+            let mut rx = manager.query(query.clone()).await;
+            match rx.recv().await {
+                Some(Err(Error::TooManyQueries(_))) => tokio::task::yield_now().await,
+                ret => {
+                    assert!(ret.unwrap().is_ok());
+                    break rx;
+                }
+            }
+        };
+        assert!(rx.recv().await.unwrap().is_ok());
+        assert!(rx.recv().await.unwrap().is_ok());
+        assert_eq!(rx.recv().await, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn stats_should_aggregate_by_day() -> Result<(), Error> {
+        let tdb = get_tdb();
+        let pool = tdb.get_pool().await;
+        let manager = ReservationManager::new(pool.clone());
+        for (start, end) in [
+            ("2030-01-10T10:00:00+0000", "2030-01-10T12:00:00+0000"),
+            ("2030-01-10T14:00:00+0000", "2030-01-10T15:00:00+0000"),
+            // across midnight
+            ("2030-01-11T22:00:00+0000", "2030-01-12T02:00:00+0000"),
+        ] {
+            make_reservation(&pool, "james id", "stats room", start, end, "").await;
+        }
+
+        let query = abi::StatsQuery {
+            resource_ids: vec!["stats room".to_string()],
+            start: Some("2030-01-10T00:00:00Z".parse::<Timestamp>().unwrap()),
+            end: Some("2030-01-13T00:00:00Z".parse::<Timestamp>().unwrap()),
+            interval: abi::StatsInterval::Day as i32,
+            ..Default::default()
+        };
+        let stats = manager.stats(query).await?;
+
+        let summary: Vec<_> = stats
+            .iter()
+            .map(|s| {
+                (
+                    s.bucket.clone().unwrap().seconds,
+                    s.reservations,
+                    s.booked_hours,
+                    s.peak_quantity,
+                )
+            })
+            .collect();
+        let day = |d: &str| d.parse::<Timestamp>().unwrap().seconds;
+        assert_eq!(
+            summary,
+            vec![
+                (day("2030-01-10T00:00:00Z"), 2, 3.0, 1),
+                (day("2030-01-11T00:00:00Z"), 1, 2.0, 1),
+                (day("2030-01-12T00:00:00Z"), 1, 2.0, 1),
+            ]
+        );
+        assert_eq!(stats[0].peak_occupancy, 1.0);
+        // none of them has started yet
+        assert_eq!(stats[0].no_show_rate, 0.0);
+        assert!(stats[0].avg_lead_hours > 0.0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn stats_should_ignore_expired_holds() -> Result<(), Error> {
+        let tdb = get_tdb();
+        let pool = tdb.get_pool().await;
+        let (hold, manager) = make_reservation(
+            &pool,
+            "james id",
+            "stats room",
+            "2022-12-25T10:00:00+0000",
+            "2022-12-25T12:00:00+0000",
+            "",
+        )
+        .await;
+        sqlx::query("UPDATE rsvp.reservations SET status = 'expired' WHERE id = $1")
+            .bind(hold.id)
+            .execute(&pool)
+            .await?;
+        // the window of the lapsed hold is booked again, and taken up
+        let (rsvp, _) = make_reservation(
+            &pool,
+            "alice id",
+            "stats room",
+            "2022-12-25T10:00:00+0000",
+            "2022-12-25T12:00:00+0000",
+            "",
+        )
+        .await;
+        manager.change_status(rsvp.id).await?;
+
+        let query = abi::StatsQuery {
+            resource_ids: vec!["stats room".to_string()],
+            start: Some("2022-12-25T00:00:00Z".parse::<Timestamp>().unwrap()),
+            end: Some("2022-12-26T00:00:00Z".parse::<Timestamp>().unwrap()),
+            interval: abi::StatsInterval::Day as i32,
+            ..Default::default()
+        };
+        let stats = manager.stats(query.clone()).await?;
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].reservations, 1);
+        assert_eq!(stats[0].booked_hours, 2.0);
+        assert_eq!(stats[0].peak_quantity, 1);
+        assert_eq!(stats[0].peak_occupancy, 1.0);
+        assert_eq!(stats[0].no_show_rate, 0.0);
+
+        // asked for, expired holds are counted but still aren't no-shows
+        let query = abi::StatsQuery {
+            statuses: vec![
+                ReservationStatus::Confirmed as i32,
+                ReservationStatus::Expired as i32,
+            ],
+            ..query
+        };
+        let stats = manager.stats(query).await?;
+        assert_eq!(stats[0].reservations, 2);
+        assert_eq!(stats[0].no_show_rate, 0.0);
 
         Ok(())
     }
@@ -330,7 +1889,199 @@ mod tests {
 
         assert_eq!(pager.prev, None);
         assert_eq!(pager.next, None);
+        assert_eq!(pager.total, None);
+        assert_eq!(rsvps.len(), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn filter_should_match_any_of_repeated_values() -> Result<(), Error> {
+        let tdb = get_tdb();
+        let pool = tdb.get_pool().await;
+        let manager = ReservationManager::new(pool.clone());
+        let mut rsvps = vec![];
+        for i in 0..4 {
+            let (rsvp, _) = make_reservation(
+                &pool,
+                "james id",
+                &format!("room {i}"),
+                "2022-12-25T15:00:00-0700",
+                "2022-12-30T00:00:00-0700",
+                "",
+            )
+            .await;
+            rsvps.push(rsvp);
+        }
+        manager.change_status(rsvps[1].id).await?;
+
+        let filter = ReservationFilterBuilder::default()
+            .resource_ids(vec![
+                "room 0".to_string(),
+                "room 1".to_string(),
+                "room 3".to_string(),
+            ])
+            .statuses(vec![
+                ReservationStatus::Pending as i32,
+                ReservationStatus::Confirmed as i32,
+            ])
+            .build()
+            .unwrap();
+        let (_, found) = manager.filter(filter.clone()).await?;
+        let ids: Vec<_> = found.iter().map(|r| r.id).collect();
+        assert_eq!(ids, vec![rsvps[0].id, rsvps[1].id, rsvps[3].id]);
+
+        // the single-value status still applies, together with the statuses
+        let filter = ReservationFilter {
+            status: ReservationStatus::Confirmed as i32,
+            statuses: vec![],
+            ..filter
+        };
+        let (_, found) = manager.filter(filter).await?;
+        let ids: Vec<_> = found.iter().map(|r| r.id).collect();
+        assert_eq!(ids, vec![rsvps[1].id]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn filter_reservations_with_total_should_work() -> Result<(), Error> {
+        let tdb = get_tdb();
+        let pool = tdb.get_pool().await;
+        let manager = ReservationManager::new(pool.clone());
+        for i in 0..25 {
+            make_reservation(
+                &pool,
+                "james id",
+                &format!("room {i}"),
+                "2022-12-25T15:00:00-0700",
+                "2022-12-30T00:00:00-0700",
+                "",
+            )
+            .await;
+        }
+
+        let filter = ReservationFilterBuilder::default()
+            .user_id("james id")
+            .include_total(true)
+            .build()
+            .unwrap();
+        let (pager, rsvps) = manager.filter(filter.clone()).await?;
+        assert_eq!(rsvps.len(), 10);
+        assert_eq!(pager.total, Some(25));
+
+        // the total doesn't change with pages
+        let filter = filter.next_page(&pager).unwrap();
+        let (pager, _) = manager.filter(filter.clone()).await?;
+        assert_eq!(pager.total, Some(25));
+
+        let filter = ReservationFilter {
+            approximate_total: true,
+            ..filter
+        };
+        let (pager, _) = manager.filter(filter).await?;
+        assert!(pager.total.unwrap() >= 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn filter_sorted_by_start_should_page_with_tokens() -> Result<(), Error> {
+        let tdb = get_tdb();
+        let pool = tdb.get_pool().await;
+        let manager = ReservationManager::new(pool.clone());
+        // later reservations start earlier, two of them start at the same time
+        for i in 0..25 {
+            let day = 28 - i.min(24);
+            make_reservation(
+                &pool,
+                "james id",
+                &format!("room {i}"),
+                &format!("2030-01-{day:02}T15:00:00-0700"),
+                "2030-01-30T00:00:00-0700",
+                "",
+            )
+            .await;
+        }
+
+        let mut filter = ReservationFilterBuilder::default()
+            .user_id("james id")
+            .sort_by(abi::ReservationSortBy::Start)
+            .build()
+            .unwrap();
+        let mut rsvps = vec![];
+        loop {
+            let (pager, page) = manager.filter(filter.clone()).await?;
+            rsvps.extend(page);
+            if pager.next_token.is_empty() {
+                break;
+            }
+            filter.page_token = pager.next_token;
+        }
+
+        assert_eq!(rsvps.len(), 25);
+        let keys: Vec<_> = rsvps
+            .iter()
+            .map(|r| (r.start.clone().unwrap().seconds, r.id))
+            .collect();
+        let mut sorted = keys.clone();
+        sorted.sort();
+        assert_eq!(keys, sorted);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn filter_should_search_time_range_and_note() -> Result<(), Error> {
+        let tdb = get_tdb();
+        let pool = tdb.get_pool().await;
+        let manager = ReservationManager::new(pool.clone());
+        let notes = [
+            "Need wheelchair access please",
+            "Two wheelchairs, arriving late",
+            "Quiet room facing the sea",
+        ];
+        for (i, note) in notes.iter().enumerate() {
+            make_reservation(
+                &pool,
+                "james id",
+                &format!("room {i}"),
+                "2030-02-01T15:00:00-0700",
+                "2030-02-03T12:00:00-0700",
+                note,
+            )
+            .await;
+        }
+        // outside of the time range
+        make_reservation(
+            &pool,
+            "james id",
+            "room 0",
+            "2030-03-01T15:00:00-0700",
+            "2030-03-03T12:00:00-0700",
+            "wheelchair",
+        )
+        .await;
+
+        let filter = ReservationFilterBuilder::default()
+            .start("2030-02-01T00:00:00-0700".parse::<Timestamp>().unwrap())
+            .end("2030-03-01T00:00:00-0700".parse::<Timestamp>().unwrap())
+            .note_query("wheelchair")
+            .include_total(true)
+            .build()
+            .unwrap();
+        let (pager, rsvps) = manager.filter(filter.clone()).await?;
+        assert_eq!(pager.total, Some(2));
+        let found: Vec<_> = rsvps.iter().map(|r| r.note.as_str()).collect();
+        assert_eq!(found, &notes[..2]);
+
+        let filter = ReservationFilter {
+            note_query: "wheelchair -late".into(),
+            ..filter
+        };
+        let (_, rsvps) = manager.filter(filter).await?;
         assert_eq!(rsvps.len(), 1);
+        assert_eq!(rsvps[0].note, notes[0]);
 
         Ok(())
     }
@@ -369,6 +2120,13 @@ mod tests {
     ) -> (abi::Reservation, ReservationManager) {
         let manager = ReservationManager::new(pool.clone());
 
+        if manager.get_resource(rid.into()).await.is_err() {
+            manager
+                .create_resource(abi::Resource::new(rid, "room", rid, 1))
+                .await
+                .unwrap();
+        }
+
         let rsvp = abi::Reservation::new_pending(
             uid,
             rid,
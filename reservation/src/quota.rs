@@ -0,0 +1,79 @@
+use std::ops::Bound;
+
+use abi::{convert_to_utc_time, Quota, QuotaUsage};
+use chrono::{DateTime, Utc};
+use sqlx::{postgres::types::PgRange, Executor, Postgres, Transaction};
+
+use crate::{trace::Traced, Error};
+
+/// check the quota of the user for a new reservation. Quota checks of the same user are
+/// serialized by an advisory lock held until the transaction ends.
+pub(crate) async fn check_quota(
+    tx: &mut Transaction<'_, Postgres>,
+    tenant_id: &str,
+    quota: &Quota,
+    rsvp: &abi::Reservation,
+) -> Result<(), Error> {
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+        .bind(format!("rsvp.quota:{}:{}", tenant_id, rsvp.user_id))
+        .execute(Traced(&mut *tx))
+        .await?;
+
+    let start = convert_to_utc_time(rsvp.start.as_ref().unwrap());
+    let end = convert_to_utc_time(rsvp.end.as_ref().unwrap());
+    let week = Quota::week_of(start);
+
+    // only the part in the week the reservation starts in counts
+    let end = match week.end {
+        Bound::Excluded(week_end) => end.min(week_end),
+        _ => end,
+    };
+    let hours = (end - start).num_seconds() as f64 / 3600.0;
+
+    let usage = quota_usage(
+        &mut *tx,
+        tenant_id,
+        &rsvp.user_id,
+        &quota.resource_type,
+        &week,
+        &rsvp.get_timespan(),
+    )
+    .await?;
+
+    quota.check(&rsvp.user_id, &usage, hours)
+}
+
+/// reservations of the user of the tenant on resources of the type. `during` is the window
+/// for concurrent reservations
+pub(crate) async fn quota_usage<'c, E>(
+    executor: E,
+    tenant_id: &str,
+    user_id: &str,
+    resource_type: &str,
+    week: &PgRange<DateTime<Utc>>,
+    during: &PgRange<DateTime<Utc>>,
+) -> Result<QuotaUsage, Error>
+where
+    E: Executor<'c, Database = Postgres>,
+{
+    let usage = sqlx::query_as(
+        "SELECT
+            COUNT(*) FILTER (WHERE upper(r.timespan) > now()) AS active,
+            COALESCE(SUM(EXTRACT(EPOCH FROM upper(r.timespan * $3) - lower(r.timespan * $3)))
+                FILTER (WHERE r.timespan && $3), 0)::float8 / 3600 AS week_hours,
+            COUNT(*) FILTER (WHERE r.timespan && $4) AS concurrent
+        FROM rsvp.reservations r JOIN rsvp.resources s ON r.tenant_id = s.tenant_id AND r.resource_id = s.id
+        WHERE r.tenant_id = $5 AND r.user_id = $1 AND s.resource_type = $2
+            AND r.status IN ('pending', 'confirmed')
+            AND (r.expires_at IS NULL OR r.expires_at > now())",
+    )
+    .bind(user_id)
+    .bind(resource_type)
+    .bind(week)
+    .bind(during)
+    .bind(tenant_id)
+    .fetch_one(Traced(executor))
+    .await?;
+
+    Ok(usage)
+}
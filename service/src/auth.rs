@@ -0,0 +1,293 @@
+use std::sync::Arc;
+
+use abi::{AuthConfig, JwtAlgorithm};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use tonic::{service::Interceptor, Request, Status};
+
+use crate::{policy::ADMIN_ROLE, tls::certificate_subject};
+
+/// identity of the caller, put in the request extensions by the interceptor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Caller {
+    pub user_id: String,
+    pub roles: Vec<String>,
+    /// ids of the resources the caller manages
+    pub resources: Vec<String>,
+    /// the caller only sees the reservations and resources of its tenant
+    pub tenant_id: String,
+}
+
+/// claims of the bearer tokens, `sub` is the user id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    pub exp: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
+    #[serde(default)]
+    pub roles: Vec<String>,
+    /// ids of the resources the user manages
+    #[serde(default)]
+    pub resources: Vec<String>,
+    /// tenant of the user, the default tenant if missing
+    #[serde(default)]
+    pub tenant: String,
+}
+
+/// validates the bearer tokens with the key from the config
+pub struct Authenticator {
+    key: DecodingKey,
+    validation: Validation,
+}
+
+/// tonic interceptor which rejects requests without a valid bearer token. Callers without
+/// a token are identified by their client certificate if any, e.g. other services over
+/// mutual TLS. Without an authenticator, i.e. with `insecure_disable_auth`, the other
+/// requests are made by `Caller::anonymous`
+#[derive(Clone)]
+pub struct AuthInterceptor {
+    auth: Option<Arc<Authenticator>>,
+    /// roles of the callers identified by their client certificate
+    client_roles: Vec<String>,
+}
+
+impl Authenticator {
+    pub fn new(config: &AuthConfig) -> Result<Self, anyhow::Error> {
+        let (key, algorithm) = match config.algorithm {
+            JwtAlgorithm::HS256 => {
+                if config.secret.is_empty() {
+                    anyhow::bail!("auth.secret is required by HS256");
+                }
+                (
+                    DecodingKey::from_secret(config.secret.as_bytes()),
+                    Algorithm::HS256,
+                )
+            }
+            JwtAlgorithm::RS256 => (
+                DecodingKey::from_rsa_pem(config.public_key.as_bytes())?,
+                Algorithm::RS256,
+            ),
+        };
+
+        let mut validation = Validation::new(algorithm);
+        if let Some(issuer) = &config.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        if let Some(audience) = &config.audience {
+            validation.set_audience(&[audience]);
+        }
+
+        Ok(Self { key, validation })
+    }
+
+    pub fn authenticate(&self, token: &str) -> Result<Caller, jsonwebtoken::errors::Error> {
+        let data = jsonwebtoken::decode::<Claims>(token, &self.key, &self.validation)?;
+
+        Ok(Caller {
+            user_id: data.claims.sub,
+            roles: data.claims.roles,
+            resources: data.claims.resources,
+            tenant_id: data.claims.tenant,
+        })
+    }
+}
+
+impl Caller {
+    /// caller of the requests without a token if authentication is disabled, an admin of the
+    /// default tenant
+    pub fn anonymous() -> Self {
+        Self {
+            user_id: "anonymous".into(),
+            roles: vec![ADMIN_ROLE.into()],
+            resources: vec![],
+            tenant_id: String::new(),
+        }
+    }
+}
+
+impl AuthInterceptor {
+    /// `auth` is only none if authentication is disabled explicitly
+    pub fn new(auth: Option<Authenticator>) -> Self {
+        Self {
+            auth: auth.map(Arc::new),
+            client_roles: vec![],
+        }
+    }
+
+    pub fn with_client_roles(self, client_roles: Vec<String>) -> Self {
+        Self {
+            client_roles,
+            ..self
+        }
+    }
+
+    /// caller identified by the subject of the verified client certificate
+    fn client_caller<T>(&self, request: &Request<T>) -> Option<Caller> {
+        let certs = request.peer_certs()?;
+        let subject = certificate_subject(certs.first()?.get_ref())?;
+
+        Some(Caller {
+            user_id: subject,
+            roles: self.client_roles.clone(),
+            resources: vec![],
+            tenant_id: String::new(),
+        })
+    }
+}
+
+impl Interceptor for AuthInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let token = request
+            .metadata()
+            .get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "));
+
+        let caller = match (&self.auth, token) {
+            (Some(auth), Some(token)) => auth
+                .authenticate(token)
+                .map_err(|e| Status::unauthenticated(format!("invalid token: {e}")))?,
+            (auth, _) => match self.client_caller(&request) {
+                Some(caller) => caller,
+                None if auth.is_some() => {
+                    return Err(Status::unauthenticated("missing bearer token"))
+                }
+                None => Caller::anonymous(),
+            },
+        };
+        request.extensions_mut().insert(caller);
+
+        Ok(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mint_token;
+    use jsonwebtoken::{EncodingKey, Header};
+    use tonic::Code;
+
+    fn hs256_config() -> AuthConfig {
+        AuthConfig {
+            algorithm: JwtAlgorithm::HS256,
+            secret: "secret".into(),
+            public_key: String::new(),
+            issuer: Some("issuer".into()),
+            audience: None,
+        }
+    }
+
+    fn call(interceptor: &mut AuthInterceptor, token: Option<&str>) -> Result<Caller, Code> {
+        let mut request = Request::new(());
+        if let Some(token) = token {
+            request
+                .metadata_mut()
+                .insert("authorization", format!("Bearer {token}").parse().unwrap());
+        }
+        let request = interceptor.call(request).map_err(|e| e.code())?;
+        Ok(request.extensions().get::<Caller>().unwrap().clone())
+    }
+
+    #[test]
+    fn interceptor_should_accept_valid_hs256_token() {
+        let config = hs256_config();
+        let mut interceptor = AuthInterceptor::new(Some(Authenticator::new(&config).unwrap()));
+        let token = mint_token(&config, "alice id", &["admin"]);
+
+        let caller = call(&mut interceptor, Some(&token)).unwrap();
+        assert_eq!(caller.user_id, "alice id");
+        assert_eq!(caller.roles, vec!["admin"]);
+    }
+
+    #[test]
+    fn interceptor_should_reject_invalid_tokens() {
+        let config = hs256_config();
+        let mut interceptor = AuthInterceptor::new(Some(Authenticator::new(&config).unwrap()));
+
+        let err = call(&mut interceptor, None).unwrap_err();
+        assert_eq!(err, Code::Unauthenticated);
+
+        let other = AuthConfig {
+            secret: "another secret".into(),
+            ..hs256_config()
+        };
+        let token = mint_token(&other, "alice id", &[]);
+        let err = call(&mut interceptor, Some(&token)).unwrap_err();
+        assert_eq!(err, Code::Unauthenticated);
+
+        let other = AuthConfig {
+            issuer: Some("another issuer".into()),
+            ..hs256_config()
+        };
+        let token = mint_token(&other, "alice id", &[]);
+        let err = call(&mut interceptor, Some(&token)).unwrap_err();
+        assert_eq!(err, Code::Unauthenticated);
+
+        let expired = Claims {
+            sub: "alice id".into(),
+            exp: 1,
+            iss: Some("issuer".into()),
+            aud: None,
+            roles: vec![],
+            resources: vec![],
+            tenant: String::new(),
+        };
+        let token = jsonwebtoken::encode(
+            &Header::default(),
+            &expired,
+            &EncodingKey::from_secret(b"secret"),
+        )
+        .unwrap();
+        let err = call(&mut interceptor, Some(&token)).unwrap_err();
+        assert_eq!(err, Code::Unauthenticated);
+    }
+
+    #[test]
+    fn interceptor_should_accept_valid_rs256_token() {
+        let config = AuthConfig {
+            algorithm: JwtAlgorithm::RS256,
+            secret: String::new(),
+            public_key: include_str!("../fixtures/jwt_rsa.pub.pem").into(),
+            issuer: None,
+            audience: Some("reservation".into()),
+        };
+        let mut interceptor = AuthInterceptor::new(Some(Authenticator::new(&config).unwrap()));
+
+        let claims = Claims {
+            sub: "bob id".into(),
+            exp: u64::MAX / 2,
+            iss: None,
+            aud: Some("reservation".into()),
+            roles: vec![],
+            resources: vec![],
+            tenant: String::new(),
+        };
+        let key = EncodingKey::from_rsa_pem(include_bytes!("../fixtures/jwt_rsa.pem")).unwrap();
+        let token = jsonwebtoken::encode(&Header::new(Algorithm::RS256), &claims, &key).unwrap();
+
+        let caller = call(&mut interceptor, Some(&token)).unwrap();
+        assert_eq!(caller.user_id, "bob id");
+
+        // an HS256 token is not accepted by an RS256 authenticator
+        let token = mint_token(&hs256_config(), "bob id", &[]);
+        let err = call(&mut interceptor, Some(&token)).unwrap_err();
+        assert_eq!(err, Code::Unauthenticated);
+    }
+
+    #[test]
+    fn interceptor_should_only_serve_anonymous_callers_if_auth_is_disabled() {
+        let mut interceptor =
+            AuthInterceptor::new(Some(Authenticator::new(&hs256_config()).unwrap()));
+        assert_eq!(
+            call(&mut interceptor, None).unwrap_err(),
+            Code::Unauthenticated
+        );
+
+        let mut interceptor = AuthInterceptor::new(None);
+        assert_eq!(call(&mut interceptor, None).unwrap(), Caller::anonymous());
+    }
+}
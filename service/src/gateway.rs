@@ -0,0 +1,337 @@
+//! REST/JSON gateway for clients which don't speak gRPC, served at `http_port`. Routes are
+//! calls to the gRPC service, so the same tokens, policies and errors apply:
+//!
+//! - `POST /reservations` with a reservation, `201` with the reservation made
+//! - `GET /reservations/{id}`
+//! - `POST /reservations/{id}/confirm`
+//! - `GET /reservations?user_id=..&status=confirmed&start=..` as `filter`
+//! - `GET /reservations/events`, the `listen` feed as Server-Sent Events
+//!
+//! Errors are `{"code": <gRPC code>, "message": ..}` with the HTTP status of the gRPC code.
+//! With `server.tls` the gateway is served over TLS with the certificates of the gRPC server,
+//! and services may be identified by their client certificate as well
+
+use std::{convert::Infallible, future::Future, net::SocketAddr, pin::Pin, sync::Arc};
+
+use abi::{
+    reservation_service_server::ReservationService, ConfirmRequest, FilterRequest, GetRequest,
+    ListenRequest, Reservation, ReservationFilterBuilder, ReservationStatus, ReserveRequest,
+};
+use axum::{
+    async_trait,
+    extract::{
+        connect_info::{ConnectInfo, Connected},
+        FromRequestParts, Path, Query, State,
+    },
+    http::{request::Parts, Request, StatusCode},
+    middleware::{self, Next},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::{get, post},
+    Json, Router,
+};
+use futures::{Stream, StreamExt};
+use hyper::server::{accept, conn::AddrStream};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::watch,
+};
+use tokio_rustls::{rustls::ServerConfig, server::TlsStream};
+use tonic::{
+    metadata::MetadataMap,
+    service::Interceptor,
+    transport::server::{Connected as _, TcpConnectInfo, TlsConnectInfo},
+    Code, Extensions, Status,
+};
+use tracing::Instrument;
+
+use crate::{auth::AuthInterceptor, telemetry, tls, RsvpService};
+
+#[derive(Clone)]
+struct Gateway {
+    svc: Arc<RsvpService>,
+    interceptor: AuthInterceptor,
+}
+
+impl Gateway {
+    /// the request as the gRPC service gets it, with the caller of the bearer token or the
+    /// client certificate
+    fn request<T>(&self, call: Call, message: T) -> Result<tonic::Request<T>, ApiError> {
+        let request = tonic::Request::from_parts(call.metadata, call.extensions, ());
+        let (metadata, extensions, _) = self.interceptor.clone().call(request)?.into_parts();
+        Ok(tonic::Request::from_parts(metadata, extensions, message))
+    }
+}
+
+/// the TLS connection info of a peer, as tonic gives it to the gRPC service
+#[derive(Clone)]
+struct Peer(Option<TlsConnectInfo<TcpConnectInfo>>);
+
+impl Connected<&AddrStream> for Peer {
+    fn connect_info(_: &AddrStream) -> Self {
+        Self(None)
+    }
+}
+
+impl Connected<&TlsStream<TcpStream>> for Peer {
+    fn connect_info(stream: &TlsStream<TcpStream>) -> Self {
+        Self(Some(stream.connect_info()))
+    }
+}
+
+/// metadata and extensions of a call, from the HTTP headers and the peer
+struct Call {
+    metadata: MetadataMap,
+    extensions: Extensions,
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Call {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        let mut extensions = Extensions::default();
+        if let Some(ConnectInfo(Peer(Some(info)))) = parts.extensions.get::<ConnectInfo<Peer>>() {
+            extensions.insert(info.clone());
+        }
+
+        Ok(Self {
+            metadata: MetadataMap::from_headers(parts.headers.clone()),
+            extensions,
+        })
+    }
+}
+
+/// serve the gateway until the shutdown resolves, over TLS if the certificates are given.
+/// Fails if the address could not be bound
+pub async fn gateway_server(
+    addr: &SocketAddr,
+    svc: Arc<RsvpService>,
+    interceptor: AuthInterceptor,
+    certs: Option<watch::Receiver<Arc<ServerConfig>>>,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> Result<Pin<Box<dyn Future<Output = Result<(), hyper::Error>> + Send>>, anyhow::Error> {
+    let app = router(Gateway { svc, interceptor }).into_make_service_with_connect_info::<Peer>();
+    Ok(match certs {
+        Some(certs) => {
+            let listener = TcpListener::bind(addr).await?;
+            let incoming = accept::from_stream(tls::incoming(listener, certs));
+            Box::pin(
+                axum::Server::builder(incoming)
+                    .serve(app)
+                    .with_graceful_shutdown(shutdown),
+            )
+        }
+        None => Box::pin(
+            axum::Server::try_bind(addr)?
+                .serve(app)
+                .with_graceful_shutdown(shutdown),
+        ),
+    })
+}
+
+fn router(gateway: Gateway) -> Router {
+    Router::new()
+        .route("/reservations", post(reserve).get(filter))
+        .route("/reservations/events", get(listen))
+        .route("/reservations/:id", get(get_reservation))
+        .route("/reservations/:id/confirm", post(confirm))
+        .layer(middleware::from_fn(trace))
+        .with_state(gateway)
+}
+
+/// run the call in a span as gRPC calls are, see `telemetry::call_span`
+async fn trace<B>(request: Request<B>, next: Next<B>) -> Response {
+    let mut head = Request::new(());
+    *head.uri_mut() = request.uri().clone();
+    *head.headers_mut() = request.headers().clone();
+    let span = telemetry::call_span(&head);
+    next.run(request).instrument(span).await
+}
+
+async fn reserve(
+    State(gateway): State<Gateway>,
+    call: Call,
+    Json(mut rsvp): Json<Reservation>,
+) -> Result<(StatusCode, Json<Reservation>), ApiError> {
+    // the status may be left out of the body, as gRPC clients always send one
+    if rsvp.status == ReservationStatus::Unknown as i32 {
+        rsvp.status = ReservationStatus::Pending as i32;
+    }
+    let request = gateway.request(call, ReserveRequest::new(rsvp))?;
+    let rsvp = gateway.svc.reserve(request).await?.into_inner().reservation;
+    Ok((StatusCode::CREATED, Json(rsvp.unwrap_or_default())))
+}
+
+async fn get_reservation(
+    State(gateway): State<Gateway>,
+    call: Call,
+    Path(id): Path<i64>,
+) -> Result<Json<Reservation>, ApiError> {
+    let request = gateway.request(call, GetRequest::new(id))?;
+    let rsvp = gateway.svc.get(request).await?.into_inner().reservation;
+    Ok(Json(rsvp.unwrap_or_default()))
+}
+
+async fn confirm(
+    State(gateway): State<Gateway>,
+    call: Call,
+    Path(id): Path<i64>,
+) -> Result<Json<Reservation>, ApiError> {
+    let request = gateway.request(call, ConfirmRequest::new(id))?;
+    let rsvp = gateway.svc.confirm(request).await?.into_inner().reservation;
+    Ok(Json(rsvp.unwrap_or_default()))
+}
+
+/// query parameters of `filter`, times are RFC 3339 and the status is a name, e.g. `pending`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct FilterParams {
+    user_id: String,
+    resource_id: String,
+    status: Option<String>,
+    page_size: Option<i64>,
+    desc: bool,
+    include_total: bool,
+    page_token: String,
+    start: Option<String>,
+    end: Option<String>,
+}
+
+impl FilterParams {
+    fn into_request(self) -> Result<FilterRequest, abi::Error> {
+        let mut builder = ReservationFilterBuilder::default();
+        builder
+            .user_id(self.user_id)
+            .resource_id(self.resource_id)
+            .desc(self.desc)
+            .include_total(self.include_total)
+            .page_token(self.page_token);
+        if let Some(status) = self.status {
+            builder.status(status.parse::<ReservationStatus>()? as i32);
+        }
+        if let Some(page_size) = self.page_size {
+            builder.page_size(page_size);
+        }
+        if let Some(start) = self.start {
+            builder.start(abi::parse_timestamp(&start)?);
+        }
+        if let Some(end) = self.end {
+            builder.end(abi::parse_timestamp(&end)?);
+        }
+
+        Ok(FilterRequest::new(builder.build()?))
+    }
+}
+
+async fn filter(
+    State(gateway): State<Gateway>,
+    call: Call,
+    Query(params): Query<FilterParams>,
+) -> Result<Json<abi::FilterResponse>, ApiError> {
+    let request = gateway.request(call, params.into_request()?)?;
+    Ok(Json(gateway.svc.filter(request).await?.into_inner()))
+}
+
+/// `reservation` events with the reservation as data, the feed ends with an `error` event,
+/// e.g. as the server shuts down
+async fn listen(
+    State(gateway): State<Gateway>,
+    call: Call,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    let request = gateway.request(call, ListenRequest {})?;
+    let stream = gateway.svc.listen(request).await?.into_inner();
+    let events = stream.map(|ret| {
+        Ok(match ret {
+            Ok(rsvp) => Event::default()
+                .event("reservation")
+                .json_data(rsvp)
+                .expect("reservations serialize to JSON"),
+            Err(status) => Event::default().event("error").data(status.message()),
+        })
+    });
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+/// a gRPC status as an HTTP response, boxed as the status is large
+#[derive(Debug)]
+struct ApiError(Box<Status>);
+
+#[derive(Serialize)]
+struct ErrorBody<'a> {
+    code: i32,
+    message: &'a str,
+}
+
+impl From<Status> for ApiError {
+    fn from(status: Status) -> Self {
+        Self(Box::new(status))
+    }
+}
+
+impl From<abi::Error> for ApiError {
+    fn from(e: abi::Error) -> Self {
+        Self(Box::new(e.into()))
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ErrorBody {
+            code: self.0.code() as i32,
+            message: self.0.message(),
+        };
+        (http_status(self.0.code()), Json(body)).into_response()
+    }
+}
+
+/// the HTTP status of a gRPC code, as in google/rpc/code.proto
+fn http_status(code: Code) -> StatusCode {
+    match code {
+        Code::Ok => StatusCode::OK,
+        Code::Cancelled => StatusCode::from_u16(499).unwrap(),
+        Code::InvalidArgument | Code::FailedPrecondition | Code::OutOfRange => {
+            StatusCode::BAD_REQUEST
+        }
+        Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+        Code::NotFound => StatusCode::NOT_FOUND,
+        Code::AlreadyExists | Code::Aborted => StatusCode::CONFLICT,
+        Code::PermissionDenied => StatusCode::FORBIDDEN,
+        Code::Unauthenticated => StatusCode::UNAUTHORIZED,
+        Code::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+        Code::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+        Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        Code::Unknown | Code::Internal | Code::DataLoss => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_params_should_parse_names_and_times() {
+        let params = FilterParams {
+            user_id: "alice".into(),
+            status: Some("confirmed".into()),
+            start: Some("2022-12-25T15:00:00-07:00".into()),
+            ..Default::default()
+        };
+        let filter = params.into_request().unwrap().filter.unwrap();
+        assert_eq!(filter.user_id, "alice");
+        assert_eq!(filter.status, ReservationStatus::Confirmed as i32);
+        assert_eq!(filter.page_size, 10);
+        assert_eq!(filter.start.unwrap().seconds, 1672005600);
+
+        let params = FilterParams {
+            status: Some("booked".into()),
+            ..Default::default()
+        };
+        let err = ApiError::from(params.into_request().unwrap_err());
+        assert_eq!(err.into_response().status(), StatusCode::BAD_REQUEST);
+    }
+}
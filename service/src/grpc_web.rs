@@ -0,0 +1,120 @@
+//! gRPC-Web for browser clients, so that pages can call the server without a proxy in
+//! between. Unary and server-streaming calls are translated to gRPC by `GrpcWebLayer`,
+//! preflight requests of other origins are answered by the CORS layer around it
+
+use std::time::Duration;
+
+use abi::GrpcWebConfig;
+use http::{header::HeaderName, HeaderValue, Method};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+/// sent by the generated clients, besides the bearer token and the trace context
+const ALLOW_HEADERS: [&str; 6] = [
+    "content-type",
+    "x-grpc-web",
+    "x-user-agent",
+    "grpc-timeout",
+    "authorization",
+    "traceparent",
+];
+
+/// the status of failed calls is sent in the headers, which pages can't read otherwise
+const EXPOSE_HEADERS: [&str; 3] = ["grpc-status", "grpc-message", "grpc-status-details-bin"];
+
+/// allows any origin, only if it's configured explicitly
+const ANY_ORIGIN: &str = "*";
+
+/// CORS for gRPC-Web calls from the allowed origins, none if the list is empty. Fails if an
+/// origin isn't a valid header
+pub fn cors(config: &GrpcWebConfig) -> Result<CorsLayer, anyhow::Error> {
+    let origins = if config.allowed_origins.iter().any(|o| o == ANY_ORIGIN) {
+        AllowOrigin::any()
+    } else {
+        let origins = config
+            .allowed_origins
+            .iter()
+            .map(|origin| origin.parse())
+            .collect::<Result<Vec<HeaderValue>, _>>()?;
+        AllowOrigin::list(origins)
+    };
+
+    Ok(CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods([Method::POST])
+        .allow_headers(ALLOW_HEADERS.map(HeaderName::from_static))
+        .expose_headers(EXPOSE_HEADERS.map(HeaderName::from_static))
+        .max_age(Duration::from_secs(config.max_age_seconds)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::future;
+    use http::{Request, Response};
+    use std::convert::Infallible;
+    use tower::{Layer, ServiceExt};
+
+    #[tokio::test]
+    async fn cors_should_allow_configured_origins() {
+        let config = GrpcWebConfig {
+            allowed_origins: vec!["https://app.example.com".into()],
+            max_age_seconds: 600,
+        };
+        let svc = cors(&config)
+            .unwrap()
+            .layer(tower::service_fn(|_: Request<()>| {
+                future::ready(Ok::<_, Infallible>(Response::new(())))
+            }));
+        let preflight = |origin: &str| {
+            Request::builder()
+                .method(Method::OPTIONS)
+                .uri("/reservation.ReservationService/query")
+                .header("origin", origin)
+                .header("access-control-request-method", "POST")
+                .header("access-control-request-headers", "authorization,x-grpc-web")
+                .body(())
+                .unwrap()
+        };
+
+        let resp = svc
+            .clone()
+            .oneshot(preflight("https://app.example.com"))
+            .await
+            .unwrap();
+        let headers = resp.headers();
+        assert_eq!(
+            headers["access-control-allow-origin"],
+            "https://app.example.com"
+        );
+        assert!(headers["access-control-allow-headers"]
+            .to_str()
+            .unwrap()
+            .contains("authorization"));
+        assert_eq!(headers["access-control-max-age"], "600");
+
+        let resp = svc
+            .oneshot(preflight("https://evil.example.com"))
+            .await
+            .unwrap();
+        assert!(resp.headers().get("access-control-allow-origin").is_none());
+
+        // no origin is allowed unless configured, any only with `*`
+        for (origins, allowed) in [(vec![], None), (vec!["*".into()], Some("*"))] {
+            let config = GrpcWebConfig {
+                allowed_origins: origins,
+                ..config.clone()
+            };
+            let svc = cors(&config)
+                .unwrap()
+                .layer(tower::service_fn(|_: Request<()>| {
+                    future::ready(Ok::<_, Infallible>(Response::new(())))
+                }));
+            let resp = svc
+                .oneshot(preflight("https://evil.example.com"))
+                .await
+                .unwrap();
+            let origin = resp.headers().get("access-control-allow-origin");
+            assert_eq!(origin.map(|v| v.to_str().unwrap()), allowed);
+        }
+    }
+}
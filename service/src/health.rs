@@ -0,0 +1,67 @@
+//! gRPC health checking. The reservation service, and the server as a whole, are reported
+//! NOT_SERVING while the database is not reachable or its migrations are behind, and
+//! from when the server shuts down
+
+use std::time::Duration;
+
+use abi::reservation_service_server::ReservationServiceServer;
+use reservation::ReservationManager;
+use tokio::{sync::watch, time};
+use tonic::server::NamedService;
+use tonic_health::{server::HealthReporter, ServingStatus};
+
+use crate::{
+    shutdown::{self, Phase},
+    RsvpService,
+};
+use tracing::warn;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// a connection which could not be acquired in time counts as unreachable
+const CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// check the database every few seconds, and report the status when it changes
+pub async fn report_health(
+    manager: ReservationManager,
+    mut reporter: HealthReporter,
+    mut phases: watch::Receiver<Phase>,
+) {
+    let name = <ReservationServiceServer<RsvpService> as NamedService>::NAME;
+    let mut interval = time::interval(CHECK_INTERVAL);
+    let mut last = None;
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown::reached(&mut phases, Phase::Draining) => {
+                // load balancers stop sending calls, watchers get the final status
+                reporter.set_service_status(name, ServingStatus::NotServing).await;
+                reporter.set_service_status("", ServingStatus::NotServing).await;
+                return;
+            }
+        }
+
+        let status = match time::timeout(CHECK_TIMEOUT, manager.check_health()).await {
+            Ok(Ok(())) => ServingStatus::Serving,
+            Ok(Err(e)) => {
+                if last != Some(ServingStatus::NotServing) {
+                    warn!("Health check failed: {e}");
+                }
+                ServingStatus::NotServing
+            }
+            Err(_) => {
+                if last != Some(ServingStatus::NotServing) {
+                    warn!("Health check timed out after {CHECK_TIMEOUT:?}");
+                }
+                ServingStatus::NotServing
+            }
+        };
+
+        if last != Some(status) {
+            reporter.set_service_status(name, status).await;
+            reporter.set_service_status("", status).await;
+            last = Some(status);
+        }
+    }
+}
@@ -1,34 +1,194 @@
+pub mod auth;
+mod gateway;
+mod grpc_web;
+mod health;
+mod metrics;
+pub mod policy;
+mod reaper;
 mod service;
+pub mod shutdown;
+pub mod telemetry;
+mod tls;
 
-use std::pin::Pin;
+use std::{future::Future, pin::Pin, sync::Arc, time::Duration};
 
-use abi::{reservation_service_server::ReservationServiceServer, Config, Reservation};
+use abi::{reservation_service_server::ReservationServiceServer, Config, Reservation, Validator};
+use auth::{AuthInterceptor, Authenticator};
 use futures::Stream;
+use metrics::{Metrics, MetricsLayer};
 use reservation::ReservationManager;
-use tokio::sync::mpsc;
-use tonic::{transport::Server, Status};
+use shutdown::Phase;
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, watch};
+use tokio::time;
+use tonic::{codegen::InterceptedService, transport::Server, Status};
+use tonic_web::GrpcWebLayer;
+use tower::util::option_layer;
+use tracing::{info, warn};
 
 #[cfg(test)]
 pub mod test_utils;
 
+/// how long the ended streams have to send their final status after the drain timeout
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(1);
+
 type ReservationStream = Pin<Box<dyn Stream<Item = Result<Reservation, Status>> + Send>>;
 
 pub struct RsvpService {
     manager: ReservationManager,
+    phases: watch::Receiver<Phase>,
+    metrics: Arc<Metrics>,
 }
 
 pub struct TonicReceiverStream<T> {
     pub inner: mpsc::Receiver<Result<T, abi::Error>>,
 }
 
+/// serve until SIGTERM or SIGINT, see `start_server_with_shutdown`
 pub async fn start_server(config: &Config) -> Result<(), anyhow::Error> {
+    start_server_with_shutdown(config, shutdown::signal()).await
+}
+
+/// serve until the signal resolves. New calls are then refused and `listen` streams ended,
+/// in-flight calls get `drain_timeout_seconds` to finish before the remaining streams are
+/// ended and the connection pool is closed
+pub async fn start_server_with_shutdown(
+    config: &Config,
+    signal: impl Future<Output = ()> + Send,
+) -> Result<(), anyhow::Error> {
+    config.validate()?;
     let addr = format!("{}:{}", config.server.host, config.server.port).parse()?;
 
-    let svc = RsvpService::from_config(config).await?;
-    let svc = ReservationServiceServer::new(svc);
+    let (phase_tx, phases) = watch::channel(Phase::Serving);
+    let svc = RsvpService::from_config(config)
+        .await?
+        .with_shutdown(phases.clone());
+    let manager = svc.manager.clone();
+    let metrics = svc.metrics.clone();
+    let reaper = tokio::spawn(reaper::reap_expired_holds(
+        manager.clone(),
+        config.rsvp.clone(),
+    ));
+    let metrics_server = match config.server.metrics_port {
+        Some(port) => {
+            let addr = format!("{}:{}", config.server.host, port).parse()?;
+            let server = metrics::metrics_server(&addr, metrics.clone(), manager.clone())?;
+            info!("Serving metrics on {addr:?}");
+            Some(tokio::spawn(server))
+        }
+        None => None,
+    };
+
+    let (reporter, health_svc) = tonic_health::server::health_reporter();
+    tokio::spawn(health::report_health(
+        manager.clone(),
+        reporter,
+        phases.clone(),
+    ));
+    let reflection_svc = tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(abi::FILE_DESCRIPTOR_SET)
+        .register_encoded_file_descriptor_set(
+            tonic_health::proto::GRPC_HEALTH_V1_FILE_DESCRIPTOR_SET,
+        )
+        .build()?;
+
+    let auth = match &config.auth {
+        Some(auth) => Some(Authenticator::new(auth)?),
+        None => {
+            warn!("Authentication is disabled by insecure_disable_auth, calls without a token are served as an admin");
+            None
+        }
+    };
+    let client_roles = match &config.server.tls {
+        Some(tls) => tls.client_roles.clone(),
+        None => vec![],
+    };
+    let interceptor = AuthInterceptor::new(auth).with_client_roles(client_roles);
+    let svc = Arc::new(svc);
+    // the gateway is served with the certificates of the gRPC server
+    let certs = match &config.server.tls {
+        Some(tls) => Some(tls::watch_certificates(tls.clone())?),
+        None => None,
+    };
+    let gateway_server = match config.server.http_port {
+        Some(port) => {
+            let addr = format!("{}:{}", config.server.host, port).parse()?;
+            let mut drained = phases.clone();
+            let server = gateway::gateway_server(
+                &addr,
+                svc.clone(),
+                interceptor.clone(),
+                certs.clone(),
+                async move { shutdown::reached(&mut drained, Phase::Draining).await },
+            )
+            .await?;
+            info!("Serving the REST gateway on {addr:?}");
+            Some(tokio::spawn(server))
+        }
+        None => None,
+    };
+    let svc = InterceptedService::new(ReservationServiceServer::from_arc(svc), interceptor);
+    let (cors, grpc_web) = match &config.server.grpc_web {
+        Some(web) => (Some(grpc_web::cors(web)?), Some(GrpcWebLayer::new())),
+        None => (None, None),
+    };
+    // probes and debugging tools don't need a token. gRPC-Web calls are translated before
+    // the metrics, which read the status from the gRPC trailers
+    let router = Server::builder()
+        .accept_http1(grpc_web.is_some())
+        .trace_fn(telemetry::call_span)
+        .layer(option_layer(cors))
+        .layer(option_layer(grpc_web))
+        .layer(MetricsLayer::new(metrics))
+        .add_service(health_svc)
+        .add_service(reflection_svc)
+        .add_service(svc);
+
+    let drain_timeout = Duration::from_secs(config.server.drain_timeout_seconds);
+    let draining = async {
+        signal.await;
+        info!("Draining in-flight calls for {drain_timeout:?}");
+        phase_tx.send_replace(Phase::Draining);
+    };
+    let serve: Pin<Box<dyn Future<Output = Result<(), tonic::transport::Error>> + Send>> =
+        match certs {
+            Some(certs) => {
+                let listener = TcpListener::bind(addr).await?;
+                info!("Listening on {addr:?} with TLS");
+                Box::pin(
+                    router.serve_with_incoming_shutdown(tls::incoming(listener, certs), draining),
+                )
+            }
+            None => {
+                info!("Listening on {addr:?}");
+                Box::pin(router.serve_with_shutdown(addr, draining))
+            }
+        };
+    tokio::pin!(serve);
+
+    let mut drained = phases.clone();
+    let deadline = async {
+        shutdown::reached(&mut drained, Phase::Draining).await;
+        time::sleep(drain_timeout).await;
+    };
+    tokio::select! {
+        ret = &mut serve => ret?,
+        _ = deadline => {
+            info!("Drain timeout is over, ending the remaining streams");
+            phase_tx.send_replace(Phase::Closed);
+            // give the streams a moment to send their final status
+            if let Ok(ret) = time::timeout(CLOSE_TIMEOUT, serve).await {
+                ret?;
+            }
+        }
+    }
 
-    println!("Listening on {addr:?}");
-    Server::builder().add_service(svc).serve(addr).await?;
+    reaper.abort();
+    for server in [metrics_server, gateway_server].into_iter().flatten() {
+        server.abort();
+    }
+    manager.pool.close().await;
+    info!("Server stopped");
 
     Ok(())
 }
@@ -2,7 +2,7 @@ use abi::Config;
 use anyhow::Result;
 use std::path::Path;
 
-use reservation_service::start_server;
+use reservation_service::{start_server, telemetry};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -23,6 +23,9 @@ async fn main() -> Result<()> {
     });
 
     let config = Config::load(filename)?;
+    telemetry::init(&config.telemetry)?;
 
-    start_server(&config).await
+    let ret = start_server(&config).await;
+    telemetry::shutdown();
+    ret
 }
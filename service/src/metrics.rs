@@ -0,0 +1,352 @@
+//! Prometheus metrics of the server, served as text on `GET /metrics` at `metrics_port`.
+//! Calls are counted and timed by a layer around the gRPC services, by the status code of
+//! the response, which is only known from the trailers for streaming calls
+
+use std::{
+    convert::Infallible,
+    future::Future,
+    net::SocketAddr,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Instant,
+};
+
+use http::{HeaderMap, Request, Response};
+use http_body::{Body, SizeHint};
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Server, StatusCode,
+};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
+    TextEncoder,
+};
+use reservation::ReservationManager;
+use tonic::{body::BoxBody, codegen::StdError, Code, Status};
+use tower::{Layer, Service};
+
+pub struct Metrics {
+    registry: Registry,
+    handled: IntCounterVec,
+    handling_seconds: HistogramVec,
+    active_calls: IntGaugeVec,
+    conflicts: IntCounterVec,
+    pool_connections: IntGauge,
+    pool_idle_connections: IntGauge,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        let call_labels = &["grpc_service", "grpc_method", "grpc_code"];
+        let handled = IntCounterVec::new(
+            Opts::new("grpc_server_handled_total", "calls completed on the server"),
+            call_labels,
+        )
+        .unwrap();
+        let handling_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "grpc_server_handling_seconds",
+                "time from the request until the last message of the response",
+            ),
+            call_labels,
+        )
+        .unwrap();
+        let active_calls = IntGaugeVec::new(
+            Opts::new(
+                "grpc_server_active_calls",
+                "calls whose response is being sent, e.g. open query and listen streams",
+            ),
+            &["grpc_service", "grpc_method"],
+        )
+        .unwrap();
+        let conflicts = IntCounterVec::new(
+            Opts::new(
+                "reservation_conflicts_total",
+                "reservations refused for overlapping others",
+            ),
+            &["resource_type"],
+        )
+        .unwrap();
+        let pool_connections =
+            IntGauge::new("db_pool_connections", "connections open in the pool").unwrap();
+        let pool_idle_connections =
+            IntGauge::new("db_pool_idle_connections", "connections idle in the pool").unwrap();
+
+        let registry = Registry::new();
+        registry.register(Box::new(handled.clone())).unwrap();
+        registry
+            .register(Box::new(handling_seconds.clone()))
+            .unwrap();
+        registry.register(Box::new(active_calls.clone())).unwrap();
+        registry.register(Box::new(conflicts.clone())).unwrap();
+        registry
+            .register(Box::new(pool_connections.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(pool_idle_connections.clone()))
+            .unwrap();
+
+        Self {
+            registry,
+            handled,
+            handling_seconds,
+            active_calls,
+            conflicts,
+            pool_connections,
+            pool_idle_connections,
+        }
+    }
+}
+
+impl Metrics {
+    pub fn count_conflict(&self, resource_type: &str) {
+        self.conflicts.with_label_values(&[resource_type]).inc();
+    }
+
+    /// the metrics in the text format, with the pool of the manager as of now
+    pub fn render(&self, manager: &ReservationManager) -> String {
+        self.pool_connections.set(manager.pool.size() as i64);
+        self.pool_idle_connections
+            .set(manager.pool.num_idle() as i64);
+        self.encode()
+    }
+
+    fn encode(&self) -> String {
+        let mut buf = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buf)
+            .unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+}
+
+/// serve the metrics on `GET /metrics`, fails if the address could not be bound
+pub fn metrics_server(
+    addr: &SocketAddr,
+    metrics: Arc<Metrics>,
+    manager: ReservationManager,
+) -> Result<impl Future<Output = Result<(), hyper::Error>>, hyper::Error> {
+    let make_svc = make_service_fn(move |_| {
+        let metrics = metrics.clone();
+        let manager = manager.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req: Request<hyper::Body>| {
+                let resp = match (req.method(), req.uri().path()) {
+                    (&http::Method::GET, "/metrics") => Response::builder()
+                        .header(http::header::CONTENT_TYPE, TextEncoder::new().format_type())
+                        .body(hyper::Body::from(metrics.render(&manager))),
+                    _ => Response::builder()
+                        .status(StatusCode::NOT_FOUND)
+                        .body(hyper::Body::empty()),
+                };
+                async move { resp }
+            }))
+        }
+    });
+
+    Ok(Server::try_bind(addr)?.serve(make_svc))
+}
+
+/// count and time the calls to the wrapped services
+#[derive(Clone)]
+pub struct MetricsLayer {
+    metrics: Arc<Metrics>,
+}
+
+impl MetricsLayer {
+    pub fn new(metrics: Arc<Metrics>) -> Self {
+        Self { metrics }
+    }
+}
+
+impl<S> Layer<S> for MetricsLayer {
+    type Service = MetricsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MetricsService {
+            inner,
+            metrics: self.metrics.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MetricsService<S> {
+    inner: S,
+    metrics: Arc<Metrics>,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for MetricsService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+    ResBody: Body<Data = hyper::body::Bytes> + Unpin + Send + 'static,
+    ResBody::Error: Into<StdError>,
+{
+    // boxed for the gRPC-Web layer around
+    type Response = Response<BoxBody>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let start = Instant::now();
+        let path = req.uri().path().to_string();
+        let metrics = self.metrics.clone();
+        let fut = self.inner.call(req);
+
+        Box::pin(async move {
+            let resp = fut.await?;
+            // errors are sent without a body, with the status in the headers
+            let code = grpc_code(resp.headers());
+            let call = Call::start(metrics, &path, start, code);
+            Ok(resp.map(|inner| {
+                MetricsBody { inner, call }
+                    .map_err(|e| Status::from_error(e.into()))
+                    .boxed_unsync()
+            }))
+        })
+    }
+}
+
+/// response body recording the call once it is finished, or dropped by a cancelled call
+pub struct MetricsBody<B> {
+    inner: B,
+    call: Call,
+}
+
+impl<B: Body + Unpin> Body for MetricsBody<B> {
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn poll_data(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        Pin::new(&mut self.inner).poll_data(cx)
+    }
+
+    fn poll_trailers(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        let ret = Pin::new(&mut self.inner).poll_trailers(cx);
+        if let Poll::Ready(Ok(Some(trailers))) = &ret {
+            if let Some(code) = grpc_code(trailers) {
+                self.call.code = Some(code);
+            }
+        }
+        ret
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+struct Call {
+    metrics: Arc<Metrics>,
+    service: String,
+    method: String,
+    start: Instant,
+    code: Option<Code>,
+}
+
+impl Call {
+    fn start(metrics: Arc<Metrics>, path: &str, start: Instant, code: Option<Code>) -> Self {
+        // unknown methods are not labelled by their path, which is up to the client
+        let (service, method) = match path.trim_start_matches('/').split_once('/') {
+            Some((service, method)) if code != Some(Code::Unimplemented) => (service, method),
+            _ => ("unknown", "unknown"),
+        };
+        metrics
+            .active_calls
+            .with_label_values(&[service, method])
+            .inc();
+
+        Self {
+            metrics,
+            service: service.to_string(),
+            method: method.to_string(),
+            start,
+            code,
+        }
+    }
+}
+
+impl Drop for Call {
+    fn drop(&mut self) {
+        let labels = [self.service.as_str(), self.method.as_str()];
+        self.metrics.active_calls.with_label_values(&labels).dec();
+
+        // no status was sent if the client went away first
+        let code = format!("{:?}", self.code.unwrap_or(Code::Cancelled));
+        let labels = [labels[0], labels[1], code.as_str()];
+        self.metrics.handled.with_label_values(&labels).inc();
+        self.metrics
+            .handling_seconds
+            .with_label_values(&labels)
+            .observe(self.start.elapsed().as_secs_f64());
+    }
+}
+
+fn grpc_code(headers: &HeaderMap) -> Option<Code> {
+    headers
+        .get("grpc-status")
+        .map(|v| Code::from_bytes(v.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::future;
+
+    #[tokio::test]
+    async fn metrics_layer_should_record_calls_by_code() {
+        let metrics = Arc::new(Metrics::default());
+        let mut svc =
+            MetricsLayer::new(metrics.clone()).layer(tower::service_fn(|req: Request<()>| {
+                let mut resp = Response::new(hyper::Body::empty());
+                if req.uri().path().ends_with("/get") {
+                    resp.headers_mut()
+                        .insert("grpc-status", "5".parse().unwrap());
+                }
+                future::ready(Ok::<_, Infallible>(resp))
+            }));
+        let request = |path: &str| Request::builder().uri(path).body(()).unwrap();
+
+        let resp = svc
+            .call(request("/reservation.ReservationService/get"))
+            .await
+            .unwrap();
+        assert!(metrics.encode().contains(
+            r#"grpc_server_active_calls{grpc_method="get",grpc_service="reservation.ReservationService"} 1"#
+        ));
+        drop(resp);
+
+        // the stream is cancelled before its trailers
+        drop(
+            svc.call(request("/reservation.ReservationService/query"))
+                .await,
+        );
+
+        let text = metrics.encode();
+        assert!(text.contains(
+            r#"grpc_server_handled_total{grpc_code="NotFound",grpc_method="get",grpc_service="reservation.ReservationService"} 1"#
+        ));
+        assert!(text.contains(
+            r#"grpc_server_handled_total{grpc_code="Cancelled",grpc_method="query",grpc_service="reservation.ReservationService"} 1"#
+        ));
+        assert!(text.contains(
+            r#"grpc_server_active_calls{grpc_method="get",grpc_service="reservation.ReservationService"} 0"#
+        ));
+    }
+}
@@ -0,0 +1,212 @@
+//! who may act on what, checked by the service before calling `Rsvp`. Users act on their own
+//! reservations, resource managers on the reservations of their resources, and admins on all.
+//! Every check fails without a caller. If authentication is disabled, the calls are made by
+//! `Caller::anonymous`, an admin
+
+use abi::Error;
+
+use crate::auth::Caller;
+
+pub const ADMIN_ROLE: &str = "admin";
+
+impl Caller {
+    pub fn is_admin(&self) -> bool {
+        self.roles.iter().any(|r| r == ADMIN_ROLE)
+    }
+
+    pub fn manages(&self, resource_id: &str) -> bool {
+        self.resources.iter().any(|r| r == resource_id)
+    }
+}
+
+/// the caller, denied if the request has none
+pub fn authenticated(caller: Option<&Caller>) -> Result<&Caller, Error> {
+    caller.ok_or_else(|| Error::PermissionDenied("the caller is not authenticated".into()))
+}
+
+/// the caller could act on a reservation of `user_id` on `resource_id`
+pub fn check_reservation(
+    caller: Option<&Caller>,
+    user_id: &str,
+    resource_id: &str,
+) -> Result<(), Error> {
+    let c = authenticated(caller)?;
+    if !c.is_admin() && c.user_id != user_id && !c.manages(resource_id) {
+        return Err(Error::PermissionDenied(format!(
+            "{} could not act on reservations of {} on {}",
+            c.user_id, user_id, resource_id
+        )));
+    }
+
+    Ok(())
+}
+
+/// the caller could act on behalf of `user_id`
+pub fn check_user(caller: Option<&Caller>, user_id: &str) -> Result<(), Error> {
+    let c = authenticated(caller)?;
+    if !c.is_admin() && c.user_id != user_id {
+        return Err(Error::PermissionDenied(format!(
+            "{} could not act on behalf of {}",
+            c.user_id, user_id
+        )));
+    }
+
+    Ok(())
+}
+
+/// the caller could change the resource, only admins and its managers could
+pub fn check_resource(caller: Option<&Caller>, resource_id: &str) -> Result<(), Error> {
+    let c = authenticated(caller)?;
+    if !c.is_admin() && !c.manages(resource_id) {
+        return Err(Error::PermissionDenied(format!(
+            "{} could not change resource {}",
+            c.user_id, resource_id
+        )));
+    }
+
+    Ok(())
+}
+
+pub fn check_admin(caller: Option<&Caller>) -> Result<(), Error> {
+    let c = authenticated(caller)?;
+    if !c.is_admin() {
+        return Err(Error::PermissionDenied(format!(
+            "{} is not an admin",
+            c.user_id
+        )));
+    }
+
+    Ok(())
+}
+
+/// check the users and resources of a search. Searches of other users are allowed on managed
+/// resources only. Returns the user the search should be scoped to if it has no users
+pub fn scope_search(
+    caller: Option<&Caller>,
+    user_ids: &[String],
+    resource_ids: &[String],
+) -> Result<Option<String>, Error> {
+    let c = authenticated(caller)?;
+
+    if c.is_admin() || (!resource_ids.is_empty() && resource_ids.iter().all(|r| c.manages(r))) {
+        return Ok(None);
+    }
+
+    if user_ids.is_empty() {
+        return Ok(Some(c.user_id.clone()));
+    }
+
+    match user_ids.iter().find(|u| **u != c.user_id) {
+        Some(other) => Err(Error::PermissionDenied(format!(
+            "{} could not search reservations of {}",
+            c.user_id, other
+        ))),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user() -> Caller {
+        Caller {
+            user_id: "alice id".into(),
+            roles: vec![],
+            resources: vec![],
+            tenant_id: String::new(),
+        }
+    }
+
+    fn manager() -> Caller {
+        Caller {
+            user_id: "bob id".into(),
+            roles: vec![],
+            resources: vec!["room 1".into()],
+            tenant_id: String::new(),
+        }
+    }
+
+    fn admin() -> Caller {
+        Caller {
+            user_id: "carol id".into(),
+            roles: vec![ADMIN_ROLE.into()],
+            resources: vec![],
+            tenant_id: String::new(),
+        }
+    }
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn user_should_act_on_own_reservations_only() {
+        let c = user();
+        assert!(check_reservation(Some(&c), "alice id", "room 1").is_ok());
+        assert!(matches!(
+            check_reservation(Some(&c), "james id", "room 1"),
+            Err(Error::PermissionDenied(_))
+        ));
+        assert!(check_user(Some(&c), "alice id").is_ok());
+        assert!(check_user(Some(&c), "james id").is_err());
+        assert!(check_resource(Some(&c), "room 1").is_err());
+        assert!(check_admin(Some(&c)).is_err());
+
+        assert_eq!(
+            scope_search(Some(&c), &[], &ids(&["room 1"])).unwrap(),
+            Some("alice id".into())
+        );
+        assert_eq!(
+            scope_search(Some(&c), &ids(&["alice id"]), &[]).unwrap(),
+            None
+        );
+        assert!(scope_search(Some(&c), &ids(&["alice id", "james id"]), &[]).is_err());
+    }
+
+    #[test]
+    fn manager_should_act_on_reservations_of_managed_resources() {
+        let c = manager();
+        assert!(check_reservation(Some(&c), "james id", "room 1").is_ok());
+        assert!(check_reservation(Some(&c), "james id", "room 2").is_err());
+        assert!(check_reservation(Some(&c), "bob id", "room 2").is_ok());
+        assert!(check_resource(Some(&c), "room 1").is_ok());
+        assert!(check_resource(Some(&c), "room 2").is_err());
+        assert!(check_admin(Some(&c)).is_err());
+
+        assert_eq!(
+            scope_search(Some(&c), &ids(&["james id"]), &ids(&["room 1"])).unwrap(),
+            None
+        );
+        assert!(scope_search(Some(&c), &ids(&["james id"]), &ids(&["room 1", "room 2"])).is_err());
+        assert_eq!(
+            scope_search(Some(&c), &[], &[]).unwrap(),
+            Some("bob id".into())
+        );
+    }
+
+    #[test]
+    fn admin_should_act_on_all() {
+        let c = admin();
+        assert!(check_reservation(Some(&c), "james id", "room 2").is_ok());
+        assert!(check_user(Some(&c), "james id").is_ok());
+        assert!(check_resource(Some(&c), "room 2").is_ok());
+        assert!(check_admin(Some(&c)).is_ok());
+        assert_eq!(scope_search(Some(&c), &[], &[]).unwrap(), None);
+    }
+
+    #[test]
+    fn no_caller_should_fail_every_check() {
+        let denied = |ret: Result<(), Error>| matches!(ret, Err(Error::PermissionDenied(_)));
+        assert!(denied(check_reservation(None, "james id", "room 2")));
+        assert!(denied(check_user(None, "james id")));
+        assert!(denied(check_resource(None, "room 2")));
+        assert!(denied(check_admin(None)));
+        assert!(denied(scope_search(None, &[], &[]).map(|_| ())));
+
+        // the caller of the calls if authentication is disabled
+        let c = Caller::anonymous();
+        assert!(check_reservation(Some(&c), "james id", "room 2").is_ok());
+        assert!(check_admin(Some(&c)).is_ok());
+    }
+}
@@ -0,0 +1,91 @@
+use std::time::Duration;
+
+use abi::RsvpConfig;
+use reservation::{ReservationManager, Rsvp};
+use tracing::warn;
+
+/// expire pending reservations whose hold is over, batch by batch. Status changes are
+/// published to the `reservation_update` channel by the reservation trigger.
+pub async fn reap_expired_holds(manager: ReservationManager, config: RsvpConfig) {
+    let batch_size = config.reaper_batch_size.max(1);
+    let mut interval =
+        tokio::time::interval(Duration::from_secs(config.reaper_interval_seconds.max(1)));
+
+    loop {
+        interval.tick().await;
+
+        loop {
+            match manager.expire_holds(batch_size).await {
+                // there may be more expired holds left. Holds confirmed in the meantime
+                // leave the batch short, the rest is left to the next tick
+                Ok(rsvps) if rsvps.len() as i64 == batch_size => continue,
+                Ok(_) => break,
+                Err(e) => {
+                    warn!("Failed to expire holds: {e:?}");
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestConfig;
+    use abi::{Reservation, ReservationStatus};
+    use reservation::ResourceCatalog;
+
+    #[tokio::test]
+    async fn reaper_should_expire_overdue_holds() {
+        let config = TestConfig::default();
+        let manager = ReservationManager::from_config(&config.db)
+            .await
+            .unwrap()
+            .with_config(config.rsvp.clone());
+        manager
+            .create_resource(abi::Resource::new("room 518", "room", "Room 518", 1))
+            .await
+            .unwrap();
+
+        let mut ids = vec![];
+        for day in 2..5 {
+            let rsvp = manager
+                .reserve(Reservation::new_pending(
+                    "alice id",
+                    "room 518",
+                    format!("2023-01-0{day}T09:00:00-0700").parse().unwrap(),
+                    format!("2023-01-0{day}T10:00:00-0700").parse().unwrap(),
+                    "",
+                ))
+                .await
+                .unwrap();
+            assert!(rsvp.expires_at.is_some());
+            ids.push(rsvp.id);
+        }
+
+        sqlx::query(
+            "UPDATE rsvp.reservations SET expires_at = now() - interval '1 second' WHERE id <> $1",
+        )
+        .bind(ids[2])
+        .execute(&manager.pool)
+        .await
+        .unwrap();
+
+        let config = RsvpConfig {
+            reaper_batch_size: 1,
+            ..config.rsvp.clone()
+        };
+        let handle = tokio::spawn(reap_expired_holds(manager.clone(), config));
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        handle.abort();
+
+        let status = |id| {
+            let manager = manager.clone();
+            async move { manager.get(id).await.unwrap().status }
+        };
+        assert_eq!(status(ids[0]).await, ReservationStatus::Expired as i32);
+        assert_eq!(status(ids[1]).await, ReservationStatus::Expired as i32);
+        assert_eq!(status(ids[2]).await, ReservationStatus::Pending as i32);
+    }
+}
@@ -1,29 +1,90 @@
 use std::{
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
 };
 
 use abi::{
-    reservation_service_server::ReservationService, CancelRequest, CancelResponse, Config,
-    ConfirmRequest, ConfirmResponse, Error, FilterRequest, FilterResponse, GetRequest, GetResponse,
-    ListenRequest, QueryRequest, ReserveRequest, ReserveResponse, UpdateRequest, UpdateResponse,
+    merge_values, reservation_service_server::ReservationService, CancelRequest, CancelResponse,
+    Config, ConfirmRequest, ConfirmResponse, CreateResourceRequest, CreateResourceResponse,
+    DeleteResourceRequest, DeleteResourceResponse, Error, FilterRequest, FilterResponse,
+    GetBookingRulesRequest, GetBookingRulesResponse, GetQuotaRequest, GetQuotaResponse, GetRequest,
+    GetResourceRequest, GetResourceResponse, GetResponse, GetStatsRequest, GetStatsResponse,
+    JoinWaitlistRequest, JoinWaitlistResponse, ListResourcesRequest, ListResourcesResponse,
+    ListenRequest, QueryRequest, ReserveRequest, ReserveResponse, SetBookingRulesRequest,
+    SetBookingRulesResponse, SetQuotaRequest, SetQuotaResponse, UpdateRequest,
+    UpdateResourceRequest, UpdateResourceResponse, UpdateResponse,
 };
 
 use futures::Stream;
-use reservation::{ReservationManager, Rsvp};
-use tokio::sync::mpsc;
+use reservation::{ReservationManager, ResourceCatalog, Rsvp};
+use tokio::sync::{mpsc, watch};
 use tonic::{async_trait, Request, Response, Status};
 
-use crate::{ReservationStream, RsvpService, TonicReceiverStream};
+use crate::{
+    auth::Caller,
+    metrics::Metrics,
+    policy,
+    shutdown::{self, Phase},
+    telemetry, ReservationStream, RsvpService, TonicReceiverStream,
+};
 
 impl RsvpService {
     pub async fn from_config(config: &Config) -> Result<Self, Error> {
         ReservationManager::from_config(&config.db)
             .await
-            .map(|m| RsvpService { manager: m })
+            .map(|m| RsvpService {
+                manager: m.with_config(config.rsvp.clone()),
+                // serving until `with_shutdown`
+                phases: watch::channel(Phase::Serving).1,
+                metrics: Arc::new(Metrics::default()),
+            })
+    }
+
+    /// end the streams of the service as the server shuts down
+    pub fn with_shutdown(self, phases: watch::Receiver<Phase>) -> Self {
+        Self { phases, ..self }
+    }
+
+    /// the manager of the tenant of the caller, the default tenant if authentication is
+    /// disabled
+    fn manager_for(&self, caller: Option<&Caller>) -> ReservationManager {
+        match caller {
+            Some(c) => self.manager.with_tenant(c.tenant_id.clone()),
+            None => self.manager.clone(),
+        }
+    }
+
+    /// count a conflict of the reservation by the type of its resource
+    async fn count_conflict(&self, caller: Option<&Caller>, err: &Error, resource_id: &str) {
+        if let Error::ConflictReservation(_) = err {
+            let resource_type = match self
+                .manager_for(caller)
+                .get_resource(resource_id.to_string())
+                .await
+            {
+                Ok(resource) => resource.resource_type,
+                Err(_) => "unknown".to_string(),
+            };
+            self.metrics.count_conflict(&resource_type);
+        }
+    }
+
+    /// the caller could act on the reservation
+    async fn check_reservation(&self, caller: Option<&Caller>, id: i64) -> Result<(), Error> {
+        // the existence of the reservation is not told to unauthenticated callers
+        policy::authenticated(caller)?;
+        let rsvp = self.manager_for(caller).get(id).await?;
+        policy::check_reservation(caller, &rsvp.user_id, &rsvp.resource_id)
     }
 }
 
+/// caller identity put by the auth interceptor, none if the request did not go through it,
+/// which fails every policy check
+fn caller<T>(request: &Request<T>) -> Option<Caller> {
+    request.extensions().get::<Caller>().cloned()
+}
+
 impl<T> Stream for TonicReceiverStream<T> {
     type Item = Result<T, Status>;
 
@@ -49,12 +110,28 @@ impl ReservationService for RsvpService {
         &self,
         request: Request<ReserveRequest>,
     ) -> Result<Response<ReserveResponse>, Status> {
+        let caller = caller(&request);
         let request = request.into_inner();
         if request.reservation.is_none() {
             return Err(Status::invalid_argument("missing reservation"));
         }
 
-        let reservation = self.manager.reserve(request.reservation.unwrap()).await?;
+        let reservation = request.reservation.unwrap();
+        policy::check_reservation(
+            caller.as_ref(),
+            &reservation.user_id,
+            &reservation.resource_id,
+        )?;
+        telemetry::record_reservation(&reservation);
+        let resource_id = reservation.resource_id.clone();
+        let reservation = match self.manager_for(caller.as_ref()).reserve(reservation).await {
+            Ok(reservation) => reservation,
+            Err(e) => {
+                self.count_conflict(caller.as_ref(), &e, &resource_id).await;
+                return Err(e.into());
+            }
+        };
+        telemetry::record_reservation(&reservation);
 
         Ok(Response::new(ReserveResponse {
             reservation: Some(reservation),
@@ -65,8 +142,15 @@ impl ReservationService for RsvpService {
         &self,
         request: Request<ConfirmRequest>,
     ) -> Result<Response<ConfirmResponse>, Status> {
+        let caller = caller(&request);
         let request = request.into_inner();
-        let reservation = self.manager.change_status(request.id).await?;
+        telemetry::record_reservation_id(request.id);
+        self.check_reservation(caller.as_ref(), request.id).await?;
+        let reservation = self
+            .manager_for(caller.as_ref())
+            .change_status(request.id)
+            .await?;
+        telemetry::record_reservation(&reservation);
         Ok(Response::new(ConfirmResponse {
             reservation: Some(reservation),
         }))
@@ -76,8 +160,15 @@ impl ReservationService for RsvpService {
         &self,
         request: Request<UpdateRequest>,
     ) -> Result<Response<UpdateResponse>, Status> {
+        let caller = caller(&request);
         let request = request.into_inner();
-        let reservation = self.manager.update_note(request.id, request.note).await?;
+        telemetry::record_reservation_id(request.id);
+        self.check_reservation(caller.as_ref(), request.id).await?;
+        let reservation = self
+            .manager_for(caller.as_ref())
+            .update_note(request.id, request.note)
+            .await?;
+        telemetry::record_reservation(&reservation);
         Ok(Response::new(UpdateResponse {
             reservation: Some(reservation),
         }))
@@ -87,16 +178,30 @@ impl ReservationService for RsvpService {
         &self,
         request: Request<CancelRequest>,
     ) -> Result<Response<CancelResponse>, Status> {
+        let caller = caller(&request);
         let request = request.into_inner();
-        let reservation = self.manager.delete(request.id).await?;
+        telemetry::record_reservation_id(request.id);
+        self.check_reservation(caller.as_ref(), request.id).await?;
+        let reservation = self.manager_for(caller.as_ref()).delete(request.id).await?;
+        telemetry::record_reservation(&reservation);
         Ok(Response::new(CancelResponse {
             reservation: Some(reservation),
         }))
     }
 
     async fn get(&self, request: Request<GetRequest>) -> Result<Response<GetResponse>, Status> {
+        let caller = caller(&request);
         let request = request.into_inner();
-        let reservation = self.manager.get(request.id).await?;
+        telemetry::record_reservation_id(request.id);
+        // the existence of the reservation is not told to unauthenticated callers
+        policy::authenticated(caller.as_ref())?;
+        let reservation = self.manager_for(caller.as_ref()).get(request.id).await?;
+        telemetry::record_reservation(&reservation);
+        policy::check_reservation(
+            caller.as_ref(),
+            &reservation.user_id,
+            &reservation.resource_id,
+        )?;
         Ok(Response::new(GetResponse {
             reservation: Some(reservation),
         }))
@@ -112,13 +217,24 @@ impl ReservationService for RsvpService {
         &self,
         request: Request<QueryRequest>,
     ) -> Result<Response<Self::queryStream>, Status> {
+        let caller = caller(&request);
         let request = request.into_inner();
 
         if request.query.is_none() {
             return Err(Status::invalid_argument("missing filter params"));
         }
 
-        let rx = self.manager.query(request.query.unwrap()).await;
+        let mut query = request.query.unwrap();
+        if let Some(user_id) = policy::scope_search(
+            caller.as_ref(),
+            &merge_values(&query.user_id, &query.user_ids),
+            &merge_values(&query.resource_id, &query.resource_ids),
+        )? {
+            query.user_ids = vec![user_id];
+        }
+        let rx = self.manager_for(caller.as_ref()).query(query).await;
+        // a query may finish while the server drains
+        let rx = shutdown::end_on(rx, self.phases.clone(), Phase::Closed);
         let stream = TonicReceiverStream::new(rx);
 
         Ok(Response::new(Box::pin(stream)))
@@ -128,13 +244,22 @@ impl ReservationService for RsvpService {
         &self,
         request: Request<FilterRequest>,
     ) -> Result<Response<FilterResponse>, Status> {
+        let caller = caller(&request);
         let request = request.into_inner();
 
         if request.filter.is_none() {
             return Err(Status::invalid_argument("missing filter params"));
         }
 
-        let (pager, rsvps) = self.manager.filter(request.filter.unwrap()).await?;
+        let mut filter = request.filter.unwrap();
+        if let Some(user_id) = policy::scope_search(
+            caller.as_ref(),
+            &merge_values(&filter.user_id, &filter.user_ids),
+            &merge_values(&filter.resource_id, &filter.resource_ids),
+        )? {
+            filter.user_ids = vec![user_id];
+        }
+        let (pager, rsvps) = self.manager_for(caller.as_ref()).filter(filter).await?;
         Ok(Response::new(FilterResponse {
             pager: Some(pager),
             reservations: rsvps,
@@ -150,9 +275,219 @@ impl ReservationService for RsvpService {
     /// another system could monitor newly added/confirmed/canceled reservation
     async fn listen(
         &self,
-        _request: Request<ListenRequest>,
+        request: Request<ListenRequest>,
     ) -> Result<Response<Self::listenStream>, Status> {
-        todo!()
+        let caller = caller(&request);
+        policy::check_admin(caller.as_ref())?;
+        let rx = self.manager_for(caller.as_ref()).listen().await;
+        // never finishes by itself, so it is ended as soon as the server drains
+        let rx = shutdown::end_on(rx, self.phases.clone(), Phase::Draining);
+        let stream = TonicReceiverStream::new(rx);
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    async fn create_resource(
+        &self,
+        request: Request<CreateResourceRequest>,
+    ) -> Result<Response<CreateResourceResponse>, Status> {
+        let caller = caller(&request);
+        policy::check_admin(caller.as_ref())?;
+        let request = request.into_inner();
+        if request.resource.is_none() {
+            return Err(Status::invalid_argument("missing resource"));
+        }
+
+        let resource = self
+            .manager_for(caller.as_ref())
+            .create_resource(request.resource.unwrap())
+            .await?;
+        Ok(Response::new(CreateResourceResponse {
+            resource: Some(resource),
+        }))
+    }
+
+    async fn update_resource(
+        &self,
+        request: Request<UpdateResourceRequest>,
+    ) -> Result<Response<UpdateResourceResponse>, Status> {
+        let caller = caller(&request);
+        let request = request.into_inner();
+        if request.resource.is_none() {
+            return Err(Status::invalid_argument("missing resource"));
+        }
+
+        let resource = request.resource.unwrap();
+        policy::check_resource(caller.as_ref(), &resource.id)?;
+        let resource = self
+            .manager_for(caller.as_ref())
+            .update_resource(resource)
+            .await?;
+        Ok(Response::new(UpdateResourceResponse {
+            resource: Some(resource),
+        }))
+    }
+
+    async fn get_resource(
+        &self,
+        request: Request<GetResourceRequest>,
+    ) -> Result<Response<GetResourceResponse>, Status> {
+        let caller = caller(&request);
+        policy::authenticated(caller.as_ref())?;
+        let request = request.into_inner();
+        let resource = self
+            .manager_for(caller.as_ref())
+            .get_resource(request.id)
+            .await?;
+        Ok(Response::new(GetResourceResponse {
+            resource: Some(resource),
+        }))
+    }
+
+    async fn delete_resource(
+        &self,
+        request: Request<DeleteResourceRequest>,
+    ) -> Result<Response<DeleteResourceResponse>, Status> {
+        let caller = caller(&request);
+        policy::check_admin(caller.as_ref())?;
+        let request = request.into_inner();
+        let resource = self
+            .manager_for(caller.as_ref())
+            .delete_resource(request.id)
+            .await?;
+        Ok(Response::new(DeleteResourceResponse {
+            resource: Some(resource),
+        }))
+    }
+
+    async fn list_resources(
+        &self,
+        request: Request<ListResourcesRequest>,
+    ) -> Result<Response<ListResourcesResponse>, Status> {
+        let caller = caller(&request);
+        policy::authenticated(caller.as_ref())?;
+        let request = request.into_inner();
+        let resources = self
+            .manager_for(caller.as_ref())
+            .list_resources(request.resource_type, request.include_inactive)
+            .await?;
+        Ok(Response::new(ListResourcesResponse { resources }))
+    }
+
+    async fn set_booking_rules(
+        &self,
+        request: Request<SetBookingRulesRequest>,
+    ) -> Result<Response<SetBookingRulesResponse>, Status> {
+        let caller = caller(&request);
+        let request = request.into_inner();
+        if request.rules.is_none() {
+            return Err(Status::invalid_argument("missing booking rules"));
+        }
+
+        let rules = request.rules.unwrap();
+        // rules of a resource type apply to resources of other managers
+        if rules.resource_id.is_empty() {
+            policy::check_admin(caller.as_ref())?;
+        } else {
+            policy::check_resource(caller.as_ref(), &rules.resource_id)?;
+        }
+        let rules = self
+            .manager_for(caller.as_ref())
+            .set_booking_rules(rules)
+            .await?;
+        Ok(Response::new(SetBookingRulesResponse {
+            rules: Some(rules),
+        }))
+    }
+
+    async fn get_booking_rules(
+        &self,
+        request: Request<GetBookingRulesRequest>,
+    ) -> Result<Response<GetBookingRulesResponse>, Status> {
+        let caller = caller(&request);
+        policy::authenticated(caller.as_ref())?;
+        let request = request.into_inner();
+        let rules = self
+            .manager_for(caller.as_ref())
+            .get_booking_rules(request.resource_id, request.resource_type)
+            .await?;
+        Ok(Response::new(GetBookingRulesResponse {
+            rules: Some(rules),
+        }))
+    }
+
+    async fn set_quota(
+        &self,
+        request: Request<SetQuotaRequest>,
+    ) -> Result<Response<SetQuotaResponse>, Status> {
+        let caller = caller(&request);
+        policy::check_admin(caller.as_ref())?;
+        let request = request.into_inner();
+        if request.quota.is_none() {
+            return Err(Status::invalid_argument("missing quota"));
+        }
+
+        let quota = self
+            .manager_for(caller.as_ref())
+            .set_quota(request.quota.unwrap())
+            .await?;
+        Ok(Response::new(SetQuotaResponse { quota: Some(quota) }))
+    }
+
+    async fn get_quota(
+        &self,
+        request: Request<GetQuotaRequest>,
+    ) -> Result<Response<GetQuotaResponse>, Status> {
+        let caller = caller(&request);
+        let request = request.into_inner();
+        policy::check_user(caller.as_ref(), &request.user_id)?;
+        let (quota, usage) = self
+            .manager_for(caller.as_ref())
+            .get_quota(request.user_id, request.resource_type)
+            .await?;
+        Ok(Response::new(GetQuotaResponse {
+            remaining_active: quota.remaining_active(&usage),
+            remaining_hours: quota.remaining_hours(&usage),
+            quota: Some(quota),
+        }))
+    }
+
+    async fn join_waitlist(
+        &self,
+        request: Request<JoinWaitlistRequest>,
+    ) -> Result<Response<JoinWaitlistResponse>, Status> {
+        let caller = caller(&request);
+        let request = request.into_inner();
+        if request.entry.is_none() {
+            return Err(Status::invalid_argument("missing waitlist entry"));
+        }
+
+        let entry = request.entry.unwrap();
+        policy::check_reservation(caller.as_ref(), &entry.user_id, &entry.resource_id)?;
+        let entry = self
+            .manager_for(caller.as_ref())
+            .join_waitlist(entry)
+            .await?;
+        Ok(Response::new(JoinWaitlistResponse { entry: Some(entry) }))
+    }
+
+    async fn get_stats(
+        &self,
+        request: Request<GetStatsRequest>,
+    ) -> Result<Response<GetStatsResponse>, Status> {
+        let caller = caller(&request);
+        let request = request.into_inner();
+        if request.query.is_none() {
+            return Err(Status::invalid_argument("missing stats query"));
+        }
+
+        let mut query = request.query.unwrap();
+        if let Some(user_id) =
+            policy::scope_search(caller.as_ref(), &query.user_ids, &query.resource_ids)?
+        {
+            query.user_ids = vec![user_id];
+        }
+        let stats = self.manager_for(caller.as_ref()).stats(query).await?;
+        Ok(Response::new(GetStatsResponse { stats }))
     }
 }
 
@@ -161,12 +496,20 @@ mod tests {
 
     use super::*;
     use crate::test_utils::TestConfig;
-    use abi::Reservation;
+    use abi::{Reservation, Resource};
 
     #[tokio::test]
     async fn rpc_reserve_should_work() {
         let config = TestConfig::default();
         let service = RsvpService::from_config(&config).await.unwrap();
+        let request = as_admin(CreateResourceRequest::new(Resource::new(
+            "Oceam view 5018",
+            "room",
+            "Oceam view room 5018",
+            1,
+        )));
+        service.create_resource(request).await.unwrap();
+
         let reservation = Reservation::new_pending(
             "james id",
             "Oceam view 5018",
@@ -174,7 +517,7 @@ mod tests {
             "2021-10-08T10:10:10-0700".parse().unwrap(),
             "test rpc reserve api",
         );
-        let request = tonic::Request::new(ReserveRequest {
+        let request = as_admin(ReserveRequest {
             reservation: Some(reservation.clone()),
         });
         let response = service.reserve(request).await.unwrap();
@@ -191,4 +534,165 @@ mod tests {
 
         // TestConfig dropped here
     }
+
+    #[tokio::test]
+    async fn rpc_resource_crud_should_work() {
+        let config = TestConfig::default();
+        let service = RsvpService::from_config(&config).await.unwrap();
+        let resource = Resource::new("parking a", "parking", "Parking lot A", 40);
+
+        let request = as_admin(CreateResourceRequest::new(resource.clone()));
+        let created = service
+            .create_resource(request)
+            .await
+            .unwrap()
+            .into_inner()
+            .resource;
+        assert_eq!(created, Some(resource.clone()));
+
+        let request = as_admin(ListResourcesRequest {
+            resource_type: "parking".into(),
+            include_inactive: false,
+        });
+        let resources = service
+            .list_resources(request)
+            .await
+            .unwrap()
+            .into_inner()
+            .resources;
+        assert_eq!(resources, vec![resource.clone()]);
+
+        let request = as_admin(DeleteResourceRequest::new("parking a"));
+        service.delete_resource(request).await.unwrap();
+
+        let request = as_admin(GetResourceRequest::new("parking a"));
+        let status = service.get_resource(request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+    }
+
+    fn as_admin<T>(message: T) -> tonic::Request<T> {
+        let mut request = tonic::Request::new(message);
+        request.extensions_mut().insert(Caller {
+            user_id: "carol id".into(),
+            roles: vec![policy::ADMIN_ROLE.into()],
+            resources: vec![],
+            tenant_id: String::new(),
+        });
+        request
+    }
+
+    fn with_caller<T>(message: T, user_id: &str, resources: &[&str]) -> tonic::Request<T> {
+        let mut request = tonic::Request::new(message);
+        request.extensions_mut().insert(Caller {
+            user_id: user_id.into(),
+            roles: vec![],
+            resources: resources.iter().map(|r| r.to_string()).collect(),
+            tenant_id: String::new(),
+        });
+        request
+    }
+
+    #[tokio::test]
+    async fn rpc_should_enforce_ownership() {
+        let config = TestConfig::default();
+        let service = RsvpService::from_config(&config).await.unwrap();
+        let resource = Resource::new("room 1", "room", "Room 1", 1);
+
+        let request = with_caller(
+            CreateResourceRequest::new(resource.clone()),
+            "james id",
+            &[],
+        );
+        let status = service.create_resource(request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+        // requests without a caller are denied
+        let request = tonic::Request::new(CreateResourceRequest::new(resource.clone()));
+        let status = service.create_resource(request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+        let request = as_admin(CreateResourceRequest::new(resource));
+        service.create_resource(request).await.unwrap();
+
+        let reservation = Reservation::new_pending(
+            "james id",
+            "room 1",
+            "2022-12-26T15:00:00-0700".parse().unwrap(),
+            "2022-12-30T12:00:00-0700".parse().unwrap(),
+            "test rpc ownership",
+        );
+        let request = with_caller(ReserveRequest::new(reservation.clone()), "alice id", &[]);
+        let status = service.reserve(request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+
+        let request = with_caller(ReserveRequest::new(reservation), "james id", &[]);
+        let rsvp = service
+            .reserve(request)
+            .await
+            .unwrap()
+            .into_inner()
+            .reservation
+            .unwrap();
+
+        let request = with_caller(CancelRequest::new(rsvp.id), "alice id", &[]);
+        let status = service.cancel(request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+        let request = tonic::Request::new(ConfirmRequest::new(rsvp.id));
+        let status = service.confirm(request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+
+        // the manager of the resource could act on the reservation of another user
+        let request = with_caller(ConfirmRequest::new(rsvp.id), "bob id", &["room 1"]);
+        service.confirm(request).await.unwrap();
+
+        let request = with_caller(CancelRequest::new(rsvp.id), "james id", &[]);
+        service.cancel(request).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn reads_without_caller_should_be_denied() {
+        let config = TestConfig::default();
+        let service = RsvpService::from_config(&config).await.unwrap();
+        let resource = Resource::new("room 1", "room", "Room 1", 1);
+        service
+            .create_resource(as_admin(CreateResourceRequest::new(resource)))
+            .await
+            .unwrap();
+        let reservation = Reservation::new_pending(
+            "james id",
+            "room 1",
+            "2022-12-26T15:00:00-0700".parse().unwrap(),
+            "2022-12-30T12:00:00-0700".parse().unwrap(),
+            "test reads without caller",
+        );
+        let rsvp = service
+            .reserve(as_admin(ReserveRequest::new(reservation)))
+            .await
+            .unwrap()
+            .into_inner()
+            .reservation
+            .unwrap();
+
+        // existing and missing reservations are denied alike
+        for id in [rsvp.id, rsvp.id + 1000] {
+            let request = tonic::Request::new(GetRequest::new(id));
+            let status = service.get(request).await.unwrap_err();
+            assert_eq!(status.code(), tonic::Code::PermissionDenied);
+        }
+
+        let request = tonic::Request::new(GetResourceRequest {
+            id: "room 1".into(),
+        });
+        let status = service.get_resource(request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+
+        let request = tonic::Request::new(ListResourcesRequest::default());
+        let status = service.list_resources(request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+
+        let request = tonic::Request::new(GetBookingRulesRequest {
+            resource_id: "room 1".into(),
+            ..Default::default()
+        });
+        let status = service.get_booking_rules(request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+    }
 }
@@ -0,0 +1,118 @@
+//! Graceful shutdown. On SIGTERM or SIGINT the server stops accepting calls, `listen`
+//! streams are ended right away, and `query` streams may finish until the drain timeout
+
+use std::future;
+
+use abi::Error;
+use tokio::{
+    signal::unix::{self, SignalKind},
+    sync::{mpsc, watch},
+};
+use tracing::info;
+
+/// lifecycle of the server, streams are ended with `Error::ShuttingDown` at a phase
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Phase {
+    Serving,
+    /// no new calls are accepted, in-flight calls may finish
+    Draining,
+    /// the drain timeout is over, the remaining streams are ended
+    Closed,
+}
+
+/// resolves on the first SIGTERM or SIGINT
+pub async fn signal() {
+    let mut term = unix::signal(SignalKind::terminate()).expect("failed to listen for SIGTERM");
+    tokio::select! {
+        _ = term.recv() => info!("Received SIGTERM, shutting down"),
+        _ = tokio::signal::ctrl_c() => info!("Received SIGINT, shutting down"),
+    }
+}
+
+/// resolves once the server reached the phase, never if the server is gone without
+/// shutting down
+pub(crate) async fn reached(phases: &mut watch::Receiver<Phase>, phase: Phase) {
+    while *phases.borrow_and_update() < phase {
+        if phases.changed().await.is_err() {
+            future::pending::<()>().await;
+        }
+    }
+}
+
+/// forward the items of a stream, ending it with `Error::ShuttingDown` once the server
+/// reached the phase, or dropping `rx` as soon as the client is gone. Dropping `rx` cancels
+/// the query or listener behind it
+pub(crate) fn end_on<T: Send + 'static>(
+    mut rx: mpsc::Receiver<Result<T, Error>>,
+    mut phases: watch::Receiver<Phase>,
+    phase: Phase,
+) -> mpsc::Receiver<Result<T, Error>> {
+    let (tx, out) = mpsc::channel(1);
+
+    tokio::spawn(async move {
+        let stop = reached(&mut phases, phase);
+        tokio::pin!(stop);
+
+        loop {
+            let item = tokio::select! {
+                item = rx.recv() => item,
+                _ = &mut stop => break,
+                _ = tx.closed() => return,
+            };
+            let Some(item) = item else {
+                return;
+            };
+            tokio::select! {
+                ret = tx.send(item) => if ret.is_err() {
+                    return;
+                },
+                _ = &mut stop => break,
+                _ = tx.closed() => return,
+            }
+        }
+
+        let _ = tx.send(Err(Error::ShuttingDown)).await;
+    });
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::time;
+
+    #[tokio::test]
+    async fn end_on_should_end_stream_at_phase() {
+        let (phase_tx, phases) = watch::channel(Phase::Serving);
+        let (tx, rx) = mpsc::channel(8);
+        let mut listen = end_on(rx, phases.clone(), Phase::Draining);
+        let (query_tx, query_rx) = mpsc::channel(8);
+        let mut query = end_on(query_rx, phases, Phase::Closed);
+
+        tx.send(Ok(1)).await.unwrap();
+        assert_eq!(listen.recv().await, Some(Ok(1)));
+
+        phase_tx.send_replace(Phase::Draining);
+        assert_eq!(listen.recv().await, Some(Err(Error::ShuttingDown)));
+        assert_eq!(listen.recv().await, None);
+        // the stream behind is cancelled
+        tx.closed().await;
+
+        // queries may finish while draining
+        query_tx.send(Ok(2)).await.unwrap();
+        assert_eq!(query.recv().await, Some(Ok(2)));
+        phase_tx.send_replace(Phase::Closed);
+        assert_eq!(query.recv().await, Some(Err(Error::ShuttingDown)));
+        assert_eq!(query.recv().await, None);
+
+        // an idle stream is cancelled once the client is gone
+        let (_phase_tx, phases) = watch::channel(Phase::Serving);
+        let (tx, rx) = mpsc::channel::<Result<i32, Error>>(8);
+        drop(end_on(rx, phases, Phase::Draining));
+        time::timeout(Duration::from_secs(1), tx.closed())
+            .await
+            .unwrap();
+    }
+}
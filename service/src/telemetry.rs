@@ -0,0 +1,137 @@
+//! Tracing of the server: a span for each call, with the SQL statements of the call as
+//! children, exported as OTLP or JSON lines. The trace context of the caller is taken from
+//! the `traceparent` metadata (W3C trace context)
+
+use abi::{Reservation, TelemetryConfig, TelemetryExporter};
+use opentelemetry::{
+    global,
+    propagation::Extractor,
+    sdk::{propagation::TraceContextPropagator, trace, Resource},
+    KeyValue,
+};
+use opentelemetry_otlp::WithExportConfig;
+use tracing::{field, info_span, Span};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::{fmt::format::FmtSpan, prelude::*, EnvFilter};
+
+/// install the global subscriber, once before the server starts
+pub fn init(config: &TelemetryConfig) -> Result<(), anyhow::Error> {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let filter =
+        EnvFilter::try_from_default_env().or_else(|_| EnvFilter::try_new(&config.filter))?;
+    let registry = tracing_subscriber::registry().with(filter);
+    match config.exporter {
+        TelemetryExporter::Json => {
+            let fmt = tracing_subscriber::fmt::layer()
+                .json()
+                .with_current_span(true)
+                .with_span_events(FmtSpan::CLOSE);
+            registry.with(fmt).try_init()?;
+        }
+        TelemetryExporter::Otlp => {
+            let tracer = opentelemetry_otlp::new_pipeline()
+                .tracing()
+                .with_exporter(
+                    opentelemetry_otlp::new_exporter()
+                        .tonic()
+                        .with_endpoint(&config.otlp_endpoint),
+                )
+                .with_trace_config(trace::config().with_resource(Resource::new(vec![
+                    KeyValue::new("service.name", config.service_name.clone()),
+                ])))
+                .install_batch(opentelemetry::runtime::Tokio)?;
+            registry
+                .with(tracing_opentelemetry::layer().with_tracer(tracer))
+                .try_init()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// export the spans not sent yet
+pub fn shutdown() {
+    global::shutdown_tracer_provider();
+}
+
+/// span of a call, a child of the span of the caller if the request carries its context.
+/// The reservation fields are recorded by the handlers, see `record_reservation`
+pub fn call_span(request: &http::Request<()>) -> Span {
+    let path = request.uri().path();
+    let span = info_span!(
+        "rpc",
+        otel.name = path,
+        otel.kind = "server",
+        rpc.system = "grpc",
+        reservation.id = field::Empty,
+        user.id = field::Empty,
+        resource.id = field::Empty,
+    );
+
+    let parent = global::get_text_map_propagator(|propagator| {
+        propagator.extract(&HeaderExtractor(request.headers()))
+    });
+    span.set_parent(parent);
+    span
+}
+
+/// record the id of the reservation the call acts on in the span of the call
+pub fn record_reservation_id(id: i64) {
+    Span::current().record("reservation.id", id);
+}
+
+/// record the reservation the call acts on in the span of the call
+pub fn record_reservation(rsvp: &Reservation) {
+    let span = Span::current();
+    if rsvp.id != 0 {
+        span.record("reservation.id", rsvp.id);
+    }
+    span.record("user.id", rsvp.user_id.as_str());
+    span.record("resource.id", rsvp.resource_id.as_str());
+}
+
+struct HeaderExtractor<'a>(&'a http::HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|v| v.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|k| k.as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::trace::{TraceContextExt, TracerProvider as _};
+
+    #[test]
+    fn call_span_should_continue_trace_of_caller() {
+        global::set_text_map_propagator(TraceContextPropagator::new());
+        // spans are only recorded with a subscriber
+        // the tracer only holds a weak reference to its provider
+        let provider = trace::TracerProvider::builder().build();
+        let tracer = provider.tracer("tests");
+        let subscriber =
+            tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(tracer));
+        tracing::subscriber::with_default(subscriber, || {
+            let request = http::Request::builder()
+                .uri("/reservation.ReservationService/get")
+                .header(
+                    "traceparent",
+                    "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+                )
+                .body(())
+                .unwrap();
+            let span = call_span(&request);
+            let cx = span.context();
+            assert_eq!(
+                cx.span().span_context().trace_id().to_string(),
+                "4bf92f3577b34da6a3ce929d0e0e4736"
+            );
+        });
+    }
+}
@@ -1,6 +1,12 @@
-use abi::Config;
+use abi::{AuthConfig, Config};
+use jsonwebtoken::{EncodingKey, Header};
+use serde::Serialize;
 use sqlx_postgres_tester::TestPg;
-use std::{ops::Deref, path::Path};
+use std::{
+    ops::Deref,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 pub struct TestConfig {
     #[allow(dead_code)]
@@ -38,3 +44,33 @@ impl Deref for TestConfig {
         &self.config
     }
 }
+
+#[derive(Serialize)]
+struct TestClaims<'a> {
+    sub: &'a str,
+    exp: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    iss: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    aud: Option<&'a str>,
+    roles: &'a [&'a str],
+}
+
+/// mint an HS256 token of the user with the secret of the config, valid for an hour
+pub fn mint_token(config: &AuthConfig, user_id: &str, roles: &[&str]) -> String {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let claims = TestClaims {
+        sub: user_id,
+        exp: now.as_secs() + 3600,
+        iss: config.issuer.as_deref(),
+        aud: config.audience.as_deref(),
+        roles,
+    };
+
+    jsonwebtoken::encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(config.secret.as_bytes()),
+    )
+    .unwrap()
+}
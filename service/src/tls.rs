@@ -0,0 +1,239 @@
+//! TLS termination of the gRPC server and the REST gateway. Certificates are reloaded when their files change,
+//! new connections are accepted with the new certificates while existing ones are kept
+
+use std::{
+    fs::{self, File},
+    io::{self, BufReader},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use abi::TlsConfig;
+use anyhow::{anyhow, Context};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::{mpsc, watch},
+    time,
+};
+use tokio_rustls::{
+    rustls::{
+        server::{AllowAnyAuthenticatedClient, NoClientAuth},
+        Certificate, PrivateKey, RootCertStore, ServerConfig,
+    },
+    server::TlsStream,
+    TlsAcceptor,
+};
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{info, warn};
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+/// connections which don't finish the handshake in time are dropped
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// load the certificates, and reload them every `reload_interval_seconds` if their files
+/// changed. A broken reload is reported and retried, the last good certificates are kept
+pub fn watch_certificates(
+    config: TlsConfig,
+) -> Result<watch::Receiver<Arc<ServerConfig>>, anyhow::Error> {
+    let mut modified = modified_times(&config);
+    let (tx, rx) = watch::channel(Arc::new(load_server_config(&config)?));
+
+    tokio::spawn(async move {
+        let mut interval =
+            time::interval(Duration::from_secs(config.reload_interval_seconds.max(1)));
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = tx.closed() => break,
+            }
+
+            let current = modified_times(&config);
+            if current == modified {
+                continue;
+            }
+
+            match load_server_config(&config) {
+                Ok(server_config) => {
+                    info!("TLS certificates reloaded from {}", config.cert);
+                    modified = current;
+                    tx.send_replace(Arc::new(server_config));
+                }
+                Err(e) => warn!("Failed to reload TLS certificates: {e:?}"),
+            }
+        }
+    });
+
+    Ok(rx)
+}
+
+/// TLS connections accepted from the listener with the latest certificates, failed
+/// handshakes are dropped without stopping the server
+pub fn incoming(
+    listener: TcpListener,
+    configs: watch::Receiver<Arc<ServerConfig>>,
+) -> ReceiverStream<Result<TlsStream<TcpStream>, io::Error>> {
+    let (tx, rx) = mpsc::channel(32);
+
+    tokio::spawn(async move {
+        loop {
+            let ret = tokio::select! {
+                ret = listener.accept() => ret,
+                _ = tx.closed() => break,
+            };
+            let (stream, peer) = match ret {
+                Ok(conn) => conn,
+                Err(e) => {
+                    // e.g. too many open files, wait for connections to close
+                    warn!("Failed to accept connection: {e:?}");
+                    time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            };
+
+            let acceptor = TlsAcceptor::from(configs.borrow().clone());
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                match time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                    Ok(Ok(stream)) => {
+                        let _ = tx.send(Ok(stream)).await;
+                    }
+                    Ok(Err(e)) => warn!("TLS handshake with {peer} failed: {e}"),
+                    Err(_) => warn!("TLS handshake with {peer} timed out"),
+                }
+            });
+        }
+    });
+
+    ReceiverStream::new(rx)
+}
+
+/// subject of a DER encoded certificate, e.g. `O=acme, CN=billing`
+pub fn certificate_subject(der: &[u8]) -> Option<String> {
+    X509Certificate::from_der(der)
+        .ok()
+        .map(|(_, cert)| cert.subject().to_string())
+}
+
+fn load_server_config(config: &TlsConfig) -> Result<ServerConfig, anyhow::Error> {
+    let certs = load_certs(&config.cert)?;
+    let key = load_key(&config.key)?;
+
+    let builder = ServerConfig::builder().with_safe_defaults();
+    let builder = match &config.client_ca {
+        Some(path) => {
+            let mut roots = RootCertStore::empty();
+            for cert in load_certs(path)? {
+                roots
+                    .add(&cert)
+                    .with_context(|| format!("invalid client CA certificate in {path}"))?;
+            }
+            builder.with_client_cert_verifier(AllowAnyAuthenticatedClient::new(roots))
+        }
+        None => builder.with_client_cert_verifier(NoClientAuth::new()),
+    };
+
+    let mut server_config = builder
+        .with_single_cert(certs, key)
+        .with_context(|| format!("invalid certificate or key in {}", config.cert))?;
+    // HTTP/1.1 for gRPC-Web and the REST gateway
+    server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok(server_config)
+}
+
+fn load_certs(path: &str) -> Result<Vec<Certificate>, anyhow::Error> {
+    let mut reader = BufReader::new(File::open(path).with_context(|| path.to_string())?);
+    let certs = rustls_pemfile::certs(&mut reader)?;
+    if certs.is_empty() {
+        return Err(anyhow!("no certificate found in {path}"));
+    }
+
+    Ok(certs.into_iter().map(Certificate).collect())
+}
+
+fn load_key(path: &str) -> Result<PrivateKey, anyhow::Error> {
+    let mut reader = BufReader::new(File::open(path).with_context(|| path.to_string())?);
+    for item in rustls_pemfile::read_all(&mut reader)? {
+        match item {
+            rustls_pemfile::Item::PKCS8Key(key)
+            | rustls_pemfile::Item::RSAKey(key)
+            | rustls_pemfile::Item::ECKey(key) => return Ok(PrivateKey(key)),
+            _ => {}
+        }
+    }
+
+    Err(anyhow!("no private key found in {path}"))
+}
+
+fn modified_times(config: &TlsConfig) -> Vec<Option<SystemTime>> {
+    [
+        Some(&config.cert),
+        Some(&config.key),
+        config.client_ca.as_ref(),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tls_config(dir: &str) -> TlsConfig {
+        TlsConfig {
+            cert: format!("{dir}/server.pem"),
+            key: format!("{dir}/server.key"),
+            client_ca: Some(format!("{dir}/ca.pem")),
+            client_roles: vec![],
+            reload_interval_seconds: 1,
+        }
+    }
+
+    #[test]
+    fn load_server_config_should_work() {
+        assert!(load_server_config(&tls_config("fixtures/tls")).is_ok());
+
+        let config = TlsConfig {
+            key: "fixtures/tls/ca.pem".into(),
+            ..tls_config("fixtures/tls")
+        };
+        assert!(load_server_config(&config).is_err());
+    }
+
+    #[test]
+    fn certificate_subject_should_work() {
+        let cert = load_certs("fixtures/tls/client.pem").unwrap().remove(0);
+        assert_eq!(
+            certificate_subject(&cert.0).unwrap(),
+            "O=reservation tests, CN=billing"
+        );
+        assert_eq!(certificate_subject(b"not a certificate"), None);
+    }
+
+    #[tokio::test]
+    async fn certificates_should_reload_on_change() {
+        let dir = std::env::temp_dir().join(format!("rsvp-tls-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for file in ["server.pem", "server.key", "ca.pem"] {
+            fs::copy(format!("fixtures/tls/{file}"), dir.join(file)).unwrap();
+        }
+
+        let mut rx = watch_certificates(tls_config(dir.to_str().unwrap())).unwrap();
+        let first = rx.borrow().clone();
+
+        // mtime resolution of some file systems is a second
+        time::sleep(Duration::from_millis(1100)).await;
+        fs::copy("fixtures/tls/server2.pem", dir.join("server.pem")).unwrap();
+        fs::copy("fixtures/tls/server2.key", dir.join("server.key")).unwrap();
+
+        time::timeout(Duration::from_secs(5), rx.changed())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!Arc::ptr_eq(&first, &rx.borrow()));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,22 +1,47 @@
 #[path = "../src/test_utils.rs"]
 mod test_utils;
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
 use abi::{
-    reservation_service_client::ReservationServiceClient, Config, ConfirmRequest, FilterRequest,
-    FilterResponse, QueryRequest, Reservation, ReservationFilterBuilder, ReservationQueryBuilder,
-    ReservationStatus, ReserveRequest,
+    reservation_service_client::ReservationServiceClient, CancelRequest, Config, ConfirmRequest,
+    CreateResourceRequest, FilterRequest, FilterResponse, GetRequest, GrpcWebConfig, ListenRequest,
+    QueryRequest, Reservation, ReservationFilter, ReservationFilterBuilder,
+    ReservationQueryBuilder, ReservationStatus, ReserveRequest, Resource, TlsConfig,
 };
 use futures::StreamExt;
-use reservation_service::start_server;
-use test_utils::TestConfig;
-use tokio::time;
-use tonic::transport::Channel;
+use prost::Message;
+use reservation_service::{start_server, start_server_with_shutdown};
+use test_utils::{mint_token, TestConfig};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+    sync::oneshot,
+    time,
+};
+use tokio_rustls::{
+    client::TlsStream,
+    rustls::{self, ClientConfig, RootCertStore, ServerName},
+    TlsConnector,
+};
+use tonic::{
+    codegen::InterceptedService,
+    service::Interceptor,
+    transport::{Certificate, Channel, ClientTlsConfig, Identity},
+    Request, Status,
+};
+use tonic_health::proto::{
+    health_check_response::ServingStatus, health_client::HealthClient, HealthCheckRequest,
+};
+use tonic_reflection::proto::{
+    server_reflection_client::ServerReflectionClient, server_reflection_request::MessageRequest,
+    server_reflection_response::MessageResponse, ServerReflectionRequest,
+};
 
 #[tokio::test]
 async fn grpc_server_should_work() {
     let tconfig = TestConfig::with_server_port(50000);
     let mut client = get_test_client(&tconfig).await;
+    make_resource(&mut client, "Ocean view room 5018").await;
 
     // first reservte a reservation
     let mut rsvp = Reservation::new_pending(
@@ -35,6 +60,10 @@ async fn grpc_server_should_work() {
         .reservation
         .unwrap();
     rsvp.id = ret.id;
+    // pending reservation holds the slot until the ttl in the config
+    assert!(ret.expires_at.is_some());
+    rsvp.expires_at = ret.expires_at.clone();
+    rsvp.created_at = ret.created_at.clone();
     assert_eq!(ret, rsvp);
 
     // then try to reserve a conflicating reservation
@@ -148,26 +177,517 @@ async fn grpc_filter_should_work() {
 
     assert_eq!(pager.next, None);
     assert_eq!(pager.prev, filter.cursor.map(|v| v + 1));
+    assert!(pager.next_token.is_empty());
 
     assert_eq!(reservations.len(), 9);
+
+    // the page token points to the same page as the raw cursor
+    let filter = ReservationFilterBuilder::default()
+        .user_id("james id")
+        .status(abi::ReservationStatus::Pending as i32)
+        .build()
+        .unwrap();
+    let by_token = ReservationFilter {
+        page_token: pager.prev_token.clone(),
+        ..filter.clone()
+    };
+    let by_cursor = ReservationFilter {
+        cursor: pager.prev,
+        ..filter
+    };
+    let by_token = client
+        .filter(FilterRequest::new(by_token))
+        .await
+        .unwrap()
+        .into_inner();
+    let by_cursor = client
+        .filter(FilterRequest::new(by_cursor))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(by_token.reservations, by_cursor.reservations);
+
+    // tokens could not be reused with another filter
+    let filter = ReservationFilterBuilder::default()
+        .user_id("alice id")
+        .status(abi::ReservationStatus::Pending as i32)
+        .page_token(pager.prev_token)
+        .build()
+        .unwrap();
+    let status = client.filter(FilterRequest::new(filter)).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+}
+
+type TestClient = ReservationServiceClient<InterceptedService<Channel, BearerToken>>;
+
+/// adds the bearer token to every request
+#[derive(Clone)]
+struct BearerToken(String);
+
+impl Interceptor for BearerToken {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let value = format!("Bearer {}", self.0).parse().unwrap();
+        request.metadata_mut().insert("authorization", value);
+        Ok(request)
+    }
+}
+
+#[tokio::test]
+async fn grpc_should_reject_requests_without_token() {
+    let tconfig = TestConfig::with_server_port(50004);
+    let channel = get_test_channel(&tconfig).await;
+    let mut client = ReservationServiceClient::new(channel.clone());
+
+    let status = client.get(GetRequest::new(1)).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::Unauthenticated);
+
+    let token = mint_token(tconfig.auth.as_ref().unwrap(), "james id", &[]);
+    let mut client = ReservationServiceClient::with_interceptor(channel, BearerToken(token));
+    let status = client.get(GetRequest::new(1)).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::NotFound);
+}
+
+#[tokio::test]
+async fn grpc_should_identify_services_by_client_certificate() {
+    let mut tconfig = TestConfig::with_server_port(50005);
+    tconfig.config.server.tls = Some(TlsConfig {
+        cert: "fixtures/tls/server.pem".into(),
+        key: "fixtures/tls/server.key".into(),
+        client_ca: Some("fixtures/tls/ca.pem".into()),
+        client_roles: vec![],
+        reload_interval_seconds: 10,
+    });
+    setup_server(&tconfig.config);
+    // the server is up once it accepts connections, the handshake comes later
+    let listening = async {
+        while TcpStream::connect(("localhost", 50005)).await.is_err() {
+            time::sleep(Duration::from_millis(10)).await;
+        }
+    };
+    time::timeout(Duration::from_secs(5), listening)
+        .await
+        .unwrap();
+
+    let ca = Certificate::from_pem(include_str!("../fixtures/tls/ca.pem"));
+    let tls = ClientTlsConfig::new()
+        .ca_certificate(ca)
+        .domain_name("localhost");
+    let endpoint = Channel::from_shared(tconfig.server.url(true)).unwrap();
+
+    // the server requires a client certificate, which fails the handshake or, with TLS 1.3,
+    // the first request
+    let channel = endpoint
+        .clone()
+        .tls_config(tls.clone())
+        .unwrap()
+        .connect_lazy();
+    time::sleep(Duration::from_millis(200)).await;
+    let mut client = ReservationServiceClient::new(channel);
+    assert!(client.get(GetRequest::new(1)).await.is_err());
+
+    let identity = Identity::from_pem(
+        include_str!("../fixtures/tls/client.pem"),
+        include_str!("../fixtures/tls/client.key"),
+    );
+    let channel = endpoint
+        .tls_config(tls.identity(identity))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = ReservationServiceClient::new(channel);
+
+    // the subject of the certificate is the caller without a bearer token
+    let rsvp = |user_id: &str| {
+        ReserveRequest::new(Reservation::new_pending(
+            user_id,
+            "Ocean view room 5018",
+            "2022-12-25T15:00:00-0700".parse().unwrap(),
+            "2022-12-30T00:00:00-0700".parse().unwrap(),
+            "reserved by a service",
+        ))
+    };
+    let status = client.reserve(rsvp("james id")).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::PermissionDenied);
+
+    let status = client
+        .reserve(rsvp("O=reservation tests, CN=billing"))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
 }
 
-async fn get_test_client(tconfig: &TestConfig) -> ReservationServiceClient<Channel> {
+#[tokio::test]
+async fn grpc_should_serve_health_and_reflection() {
+    let tconfig = TestConfig::with_server_port(50006);
+    let channel = get_test_channel(&tconfig).await;
+
+    // no token is needed, the first check of the database may still be running
+    let mut client = HealthClient::new(channel.clone());
+    let fut = async {
+        loop {
+            let request = HealthCheckRequest {
+                service: "reservation.ReservationService".into(),
+            };
+            match client.check(request).await {
+                Ok(resp) => break resp.into_inner().status,
+                Err(_) => time::sleep(Duration::from_millis(10)).await,
+            }
+        }
+    };
+    let status = time::timeout(Duration::from_secs(5), fut).await.unwrap();
+    assert_eq!(status, ServingStatus::Serving as i32);
+
+    let mut client = ServerReflectionClient::new(channel);
+    let request = ServerReflectionRequest {
+        host: "".into(),
+        message_request: Some(MessageRequest::ListServices("".into())),
+    };
+    let mut stream = client
+        .server_reflection_info(futures::stream::iter([request]))
+        .await
+        .unwrap()
+        .into_inner();
+    let services = match stream.next().await.unwrap().unwrap().message_response {
+        Some(MessageResponse::ListServicesResponse(resp)) => resp.service,
+        other => panic!("unexpected response: {other:?}"),
+    };
+    let names: Vec<_> = services.into_iter().map(|s| s.name).collect();
+    assert!(names.contains(&"reservation.ReservationService".to_string()));
+    assert!(names.contains(&"grpc.health.v1.Health".to_string()));
+}
+
+#[tokio::test]
+async fn grpc_should_shut_down_gracefully() {
+    let mut tconfig = TestConfig::with_server_port(50007);
+    tconfig.config.server.drain_timeout_seconds = 1;
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
     let config = tconfig.config.clone();
-    setup_server(&config);
+    let server = tokio::spawn(async move {
+        let signal = async {
+            let _ = shutdown_rx.await;
+        };
+        start_server_with_shutdown(&config, signal).await
+    });
+
+    let channel = connect_channel(&tconfig.config).await;
+    let token = mint_token(tconfig.auth.as_ref().unwrap(), "james id", &["admin"]);
+    let mut client = ReservationServiceClient::with_interceptor(channel, BearerToken(token));
+    let mut stream = client.listen(ListenRequest {}).await.unwrap().into_inner();
+
+    shutdown_tx.send(()).unwrap();
+
+    // the open stream gets a final status instead of being cut
+    let status = stream.next().await.unwrap().unwrap_err();
+    assert_eq!(status.code(), tonic::Code::Unavailable);
+    assert!(stream.next().await.is_none());
+
+    time::timeout(Duration::from_secs(5), server)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    let endpoint = Channel::from_shared(tconfig.server.url(false)).unwrap();
+    assert!(endpoint.connect().await.is_err());
+}
+
+#[tokio::test]
+async fn grpc_should_export_metrics() {
+    let mut tconfig = TestConfig::with_server_port(50008);
+    tconfig.config.server.metrics_port = Some(50009);
+    let mut client = get_test_client(&tconfig).await;
+    make_resource(&mut client, "Ocean view room 5018").await;
+
+    let rsvp = || {
+        ReserveRequest::new(Reservation::new_pending(
+            "james id",
+            "Ocean view room 5018",
+            "2022-12-25T15:00:00-0700".parse().unwrap(),
+            "2022-12-30T00:00:00-0700".parse().unwrap(),
+            "metrics",
+        ))
+    };
+    client.reserve(rsvp()).await.unwrap();
+    assert!(client.reserve(rsvp()).await.is_err());
+    let _stream = client.listen(ListenRequest {}).await.unwrap();
+
+    let text = scrape_metrics(50009).await;
+    assert!(text.starts_with("HTTP/1.0 200 OK"));
+    for line in [
+        r#"grpc_server_handled_total{grpc_code="Ok",grpc_method="reserve",grpc_service="reservation.ReservationService"} 1"#,
+        r#"grpc_server_handled_total{grpc_code="FailedPrecondition",grpc_method="reserve",grpc_service="reservation.ReservationService"} 1"#,
+        r#"grpc_server_active_calls{grpc_method="listen",grpc_service="reservation.ReservationService"} 1"#,
+        r#"reservation_conflicts_total{resource_type="room"} 1"#,
+        "db_pool_connections ",
+    ] {
+        assert!(text.contains(line), "{line} not found in\n{text}");
+    }
+}
+
+#[tokio::test]
+async fn rest_gateway_should_work() {
+    let mut tconfig = TestConfig::with_server_port(50010);
+    tconfig.config.server.http_port = Some(50011);
+    let mut client = get_test_client(&tconfig).await;
+    make_resource(&mut client, "Ocean view room 5018").await;
+    let token = mint_token(tconfig.auth.as_ref().unwrap(), "james id", &["admin"]);
+    let call = |method: &'static str, path: String, body: &'static str| {
+        http_call(50011, method, path, Some(token.clone()), body)
+    };
+
+    let body = r#"{"user_id": "james id", "resource_id": "Ocean view room 5018",
+        "start": "2022-12-25T15:00:00-07:00", "end": "2022-12-30T00:00:00-07:00"}"#;
+    let (status, rsvp) = call("POST", "/reservations".into(), body).await;
+    assert_eq!(status, 201);
+    assert_eq!(rsvp["status"], "pending");
+    assert_eq!(rsvp["start"], "2022-12-25T22:00:00Z");
+    let id = rsvp["id"].as_i64().unwrap();
+
+    let (status, rsvp) = call("GET", format!("/reservations/{id}"), "").await;
+    assert_eq!(status, 200);
+    assert_eq!(rsvp["user_id"], "james id");
+
+    let (status, rsvp) = call("POST", format!("/reservations/{id}/confirm"), "").await;
+    assert_eq!(status, 200);
+    assert_eq!(rsvp["status"], "confirmed");
+
+    let path = "/reservations?user_id=james%20id&status=confirmed".to_string();
+    let (status, resp) = call("GET", path, "").await;
+    assert_eq!(status, 200);
+    assert_eq!(resp["reservations"][0]["id"], id);
+
+    // conflicts and missing reservations get the HTTP status of their gRPC code
+    let (status, err) = call("POST", "/reservations".into(), body).await;
+    assert_eq!(status, 400);
+    assert_eq!(err["code"], tonic::Code::FailedPrecondition as i32);
+    let (status, err) = call("GET", "/reservations/100000".into(), "").await;
+    assert_eq!(status, 404);
+    assert_eq!(err["code"], tonic::Code::NotFound as i32);
+    let (status, _) = http_call(50011, "GET", format!("/reservations/{id}"), None, "").await;
+    assert_eq!(status, 401);
+
+    // the listen feed as Server-Sent Events
+    let mut stream = TcpStream::connect(("localhost", 50011)).await.unwrap();
+    let request =
+        format!("GET /reservations/events HTTP/1.0\r\nauthorization: Bearer {token}\r\n\r\n");
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut events = String::new();
+    let mut buf = [0; 1024];
+    while !events.contains("\r\n\r\n") {
+        let n = stream.read(&mut buf).await.unwrap();
+        events.push_str(std::str::from_utf8(&buf[..n]).unwrap());
+    }
+    assert!(events.starts_with("HTTP/1.0 200 OK"));
+    client.cancel(CancelRequest::new(id)).await.unwrap();
+    let read = async {
+        while !events.contains(&format!("data:{{\"id\":{id},")) {
+            let n = stream.read(&mut buf).await.unwrap();
+            events.push_str(std::str::from_utf8(&buf[..n]).unwrap());
+        }
+    };
+    time::timeout(Duration::from_secs(5), read).await.unwrap();
+    assert!(events.contains("event:reservation"));
+}
+
+#[tokio::test]
+async fn rest_gateway_should_be_served_over_tls() {
+    let mut tconfig = TestConfig::with_server_port(50013);
+    tconfig.config.server.http_port = Some(50014);
+    tconfig.config.server.tls = Some(TlsConfig {
+        cert: "fixtures/tls/server.pem".into(),
+        key: "fixtures/tls/server.key".into(),
+        client_ca: Some("fixtures/tls/ca.pem".into()),
+        client_roles: vec![],
+        reload_interval_seconds: 10,
+    });
+    setup_server(&tconfig.config);
+
+    let connect = async {
+        loop {
+            match tls_connect(50014).await {
+                Ok(stream) => break stream,
+                Err(_) => time::sleep(Duration::from_millis(10)).await,
+            }
+        }
+    };
+    let stream = time::timeout(Duration::from_secs(5), connect)
+        .await
+        .unwrap();
+
+    // the service is identified by its client certificate, without a bearer token
+    let path = "/reservations/100000".to_string();
+    let (status, err) = http_exchange(stream, "GET", path, None, "").await;
+    assert_eq!(status, 404);
+    assert_eq!(err["code"], tonic::Code::NotFound as i32);
+
+    // plaintext isn't served
+    let mut stream = TcpStream::connect(("localhost", 50014)).await.unwrap();
+    stream
+        .write_all(b"GET /reservations/1 HTTP/1.0\r\n\r\n")
+        .await
+        .unwrap();
+    let mut resp = vec![];
+    let _ = stream.read_to_end(&mut resp).await;
+    assert!(!resp.starts_with(b"HTTP/"));
+}
+
+#[tokio::test]
+async fn grpc_web_should_serve_streams_to_browsers() {
+    let mut tconfig = TestConfig::with_server_port(50012);
+    tconfig.config.server.grpc_web = Some(GrpcWebConfig {
+        allowed_origins: vec!["https://app.example.com".into()],
+        max_age_seconds: 600,
+    });
+    // gRPC clients are served alongside
+    let mut client = get_test_client(&tconfig).await;
+    make_reservations(&mut client, 3).await;
+    let token = mint_token(tconfig.auth.as_ref().unwrap(), "james id", &["admin"]);
+
+    let query = ReservationQueryBuilder::default()
+        .user_id("james id")
+        .build()
+        .unwrap();
+    let message = QueryRequest::new(query).encode_to_vec();
+    let mut body = vec![0];
+    body.extend_from_slice(&(message.len() as u32).to_be_bytes());
+    body.extend_from_slice(&message);
+
+    let mut stream = TcpStream::connect(("localhost", 50012)).await.unwrap();
+    let head = format!(
+        "POST /reservation.ReservationService/query HTTP/1.0\r\n\
+        origin: https://app.example.com\r\n\
+        content-type: application/grpc-web+proto\r\n\
+        x-grpc-web: 1\r\n\
+        authorization: Bearer {token}\r\n\
+        content-length: {}\r\n\r\n",
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await.unwrap();
+    stream.write_all(&body).await.unwrap();
+    let mut resp = Vec::new();
+    stream.read_to_end(&mut resp).await.unwrap();
+
+    let split = resp.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+    let head = String::from_utf8_lossy(&resp[..split]).to_lowercase();
+    assert!(head.starts_with("http/1.0 200 ok"), "{head}");
+    assert!(head.contains("access-control-allow-origin: https://app.example.com"));
+
+    // messages, then the trailers in a frame of their own
+    let mut frames = &resp[split + 4..];
+    let mut rsvps = vec![];
+    let mut trailers = String::new();
+    while !frames.is_empty() {
+        let len = u32::from_be_bytes(frames[1..5].try_into().unwrap()) as usize;
+        let payload = &frames[5..5 + len];
+        match frames[0] {
+            0 => rsvps.push(Reservation::decode(payload).unwrap()),
+            _ => trailers = String::from_utf8_lossy(payload).to_string(),
+        }
+        frames = &frames[5 + len..];
+    }
+    assert_eq!(rsvps.len(), 3);
+    assert!(rsvps.iter().all(|rsvp| rsvp.user_id == "james id"));
+    assert!(trailers.contains("grpc-status:0"), "{trailers}");
+}
+
+/// status and JSON body of a call to the REST gateway
+async fn http_call(
+    port: u16,
+    method: &str,
+    path: String,
+    token: Option<String>,
+    body: &str,
+) -> (u16, serde_json::Value) {
+    let stream = TcpStream::connect(("localhost", port)).await.unwrap();
+    http_exchange(stream, method, path, token, body).await
+}
+
+/// status and JSON body of a call over the connection
+async fn http_exchange(
+    mut stream: impl AsyncRead + AsyncWrite + Unpin,
+    method: &str,
+    path: String,
+    token: Option<String>,
+    body: &str,
+) -> (u16, serde_json::Value) {
+    let mut request = format!("{method} {path} HTTP/1.0\r\n");
+    if let Some(token) = token {
+        request.push_str(&format!("authorization: Bearer {token}\r\n"));
+    }
+    request.push_str(&format!(
+        "content-type: application/json\r\ncontent-length: {}\r\n\r\n{body}",
+        body.len()
+    ));
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut text = String::new();
+    stream.read_to_string(&mut text).await.unwrap();
+
+    let (head, body) = text.split_once("\r\n\r\n").unwrap();
+    let status = head[9..12].parse().unwrap();
+    (status, serde_json::from_str(body).unwrap_or_default())
+}
+
+/// a TLS connection with the client certificate of the fixtures
+async fn tls_connect(port: u16) -> std::io::Result<TlsStream<TcpStream>> {
+    let mut roots = RootCertStore::empty();
+    for cert in rustls_pemfile::certs(&mut include_bytes!("../fixtures/tls/ca.pem").as_ref())? {
+        roots.add(&rustls::Certificate(cert)).unwrap();
+    }
+    let certs = rustls_pemfile::certs(&mut include_bytes!("../fixtures/tls/client.pem").as_ref())?;
+    let key = rustls_pemfile::pkcs8_private_keys(
+        &mut include_bytes!("../fixtures/tls/client.key").as_ref(),
+    )?;
+    let mut config = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots)
+        .with_single_cert(
+            certs.into_iter().map(rustls::Certificate).collect(),
+            rustls::PrivateKey(key[0].clone()),
+        )
+        .unwrap();
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+
+    let stream = TcpStream::connect(("localhost", port)).await?;
+    let domain = ServerName::try_from("localhost").unwrap();
+    TlsConnector::from(Arc::new(config))
+        .connect(domain, stream)
+        .await
+}
 
+/// the response of `GET /metrics`, with its status line
+async fn scrape_metrics(port: u16) -> String {
+    let mut stream = TcpStream::connect(("localhost", port)).await.unwrap();
+    stream
+        .write_all(b"GET /metrics HTTP/1.0\r\n\r\n")
+        .await
+        .unwrap();
+    let mut text = String::new();
+    stream.read_to_string(&mut text).await.unwrap();
+    text
+}
+
+async fn get_test_client(tconfig: &TestConfig) -> TestClient {
+    let channel = get_test_channel(tconfig).await;
+    let token = mint_token(tconfig.auth.as_ref().unwrap(), "james id", &["admin"]);
+
+    ReservationServiceClient::with_interceptor(channel, BearerToken(token))
+}
+
+async fn get_test_channel(tconfig: &TestConfig) -> Channel {
+    setup_server(&tconfig.config);
+    connect_channel(&tconfig.config).await
+}
+
+async fn connect_channel(config: &Config) -> Channel {
     let fut = async move {
+        let endpoint = Channel::from_shared(config.server.url(false)).unwrap();
         // if error on conn keep retry until timeout
-        while ReservationServiceClient::connect(config.server.url(false))
-            .await
-            .is_err()
-        {
-            time::sleep(Duration::from_millis(10)).await;
+        loop {
+            match endpoint.connect().await {
+                Ok(channel) => break channel,
+                Err(_) => time::sleep(Duration::from_millis(10)).await,
+            }
         }
-
-        ReservationServiceClient::connect(config.server.url(false))
-            .await
-            .unwrap()
     };
 
     time::timeout(Duration::from_secs(5), fut).await.unwrap()
@@ -180,8 +700,16 @@ fn setup_server(config: &Config) {
     });
 }
 
-async fn make_reservations(client: &mut ReservationServiceClient<Channel>, count: i32) {
+async fn make_resource(client: &mut TestClient, id: &str) {
+    client
+        .create_resource(CreateResourceRequest::new(Resource::new(id, "room", id, 1)))
+        .await
+        .unwrap();
+}
+
+async fn make_reservations(client: &mut TestClient, count: i32) {
     for i in 0..count {
+        make_resource(client, &format!("Ocean view room {i}")).await;
         let mut rsvp = Reservation::new_pending(
             "james id",
             format!("Ocean view room {i}"),
@@ -198,6 +726,8 @@ async fn make_reservations(client: &mut ReservationServiceClient<Channel>, count
             .reservation
             .unwrap();
         rsvp.id = ret.id;
+        rsvp.expires_at = ret.expires_at.clone();
+        rsvp.created_at = ret.created_at.clone();
         assert_eq!(ret, rsvp);
     }
 }